@@ -0,0 +1,29 @@
+//! Rolling hands and building take tables.
+//!
+//! Run with `cargo bench --bench hand`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use kostka::dice_m::{Hand, Ruleset, TakeTable};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::hint::black_box;
+
+fn roll(c: &mut Criterion) {
+    let table = TakeTable::for_rules(&Ruleset::default()).unwrap();
+    c.bench_function("roll six dices", |b| {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        b.iter(|| {
+            let hand = Hand::roll(6, &table, &mut rng);
+            black_box(hand.get_takes().map(|take| take.value).max())
+        })
+    });
+}
+
+fn table(c: &mut Criterion) {
+    c.bench_function("build take table", |b| {
+        b.iter(|| TakeTable::new(black_box(&Ruleset::default())))
+    });
+}
+
+criterion_group!(benches, roll, table);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+#!/usr/bin/env python3
+"""Example strategy for `kostka`, run as `external:python3 examples/external_bot.py`.
+
+Takes the most valuable option and keeps rolling until the turn is worth 300,
+taking over the previous turn when it is worth that already.
+"""
+import json
+import sys
+
+turn_score = 0
+
+for line in sys.stdin:
+    message = json.loads(line)
+    kind = message["type"]
+
+    if kind == "new_round":
+        turn_score = 0
+    elif kind == "pick_take":
+        # takes are sorted from the most valuable
+        takes = message["takes"]
+        if takes:
+            turn_score += takes[0]["value"]
+        print(json.dumps({"take": 0 if takes else None}), flush=True)
+    elif kind == "continue_or_stop":
+        action = "stop" if turn_score >= 300 else "continue"
+        print(json.dumps({"action": action}), flush=True)
+    elif kind == "take_over":
+        score, _dices = message["state"]["previous_turn"]
+        if score >= 300:
+            turn_score = score
+        print(json.dumps({"take_over": score >= 300}), flush=True)
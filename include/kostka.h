@@ -0,0 +1,279 @@
+#ifndef KOSTKA_H
+#define KOSTKA_H
+
+/* Generated by cbindgen from src/capi_m, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Most sides of any ruleset, the length of the dices used of a take
+#define KOSTKA_MAX_SIDES 12
+
+// Most dices of any ruleset, the length of the dices of a step
+#define KOSTKA_MAX_DICES 10
+
+// Result of every call of the C API.
+//
+// Outputs are written only on `KOSTKA_STATUS_OK`, except for the count of a
+// buffer that was too small.
+typedef enum KostkaStatus {
+  KOSTKA_STATUS_OK = 0,
+  // A required pointer was NULL
+  KOSTKA_STATUS_NULL_POINTER = 1,
+  // Dices or sides outside of the supported limits
+  KOSTKA_STATUS_INVALID_RULESET = 2,
+  // A dice value the dices of the ruleset do not have
+  KOSTKA_STATUS_INVALID_DICE = 3,
+  // The take is not offered by the hand
+  KOSTKA_STATUS_INVALID_TAKE = 4,
+  // Answered a question the match did not ask
+  KOSTKA_STATUS_NOT_ASKED = 5,
+  // No player sits at the index
+  KOSTKA_STATUS_PLAYER_INDEX = 6,
+  // The buffer cannot hold every item, the count tells how many there are
+  KOSTKA_STATUS_BUFFER_TOO_SMALL = 7,
+  // Every player forfeited, nobody can win
+  KOSTKA_STATUS_ALL_FORFEITED = 8,
+  // Any other failure of the engine, panics included
+  KOSTKA_STATUS_FAILED = 9,
+  // The players cannot be split into teams of the same size
+  KOSTKA_STATUS_INVALID_TEAMS = 10,
+} KostkaStatus;
+
+typedef enum KostkaStepKind {
+  // The player starts a turn
+  KOSTKA_STEP_KIND_TURN_STARTED,
+  // The player picks a take, answer with `kostka_match_take` or
+  // `kostka_match_give_up`
+  KOSTKA_STEP_KIND_PICK_TAKE,
+  // The player may bank the turn score, answer with `kostka_match_decide`
+  KOSTKA_STEP_KIND_CONTINUE_OR_STOP,
+  // All the dices scored, the player rolls all of them again
+  KOSTKA_STEP_KIND_HOT_DICE,
+  KOSTKA_STEP_KIND_TURN_ENDED,
+  // The match is over, `player` won
+  KOSTKA_STEP_KIND_FINISHED,
+  // The player may start from the `turn_score` and `dices_available` the
+  // previous player banked with, answer with `kostka_match_take_over`
+  KOSTKA_STEP_KIND_TAKE_OVER,
+  // The match played in teams is over, `team` won
+  KOSTKA_STEP_KIND_TEAM_FINISHED,
+} KostkaStepKind;
+
+typedef enum KostkaTurnResult {
+  KOSTKA_TURN_RESULT_NOTHING,
+  // Player left the game
+  KOSTKA_TURN_RESULT_FORFEIT,
+  // Player banked `value` points
+  KOSTKA_TURN_RESULT_VALUE,
+} KostkaTurnResult;
+
+// Match stepped from C, created by `kostka_match_new` and released by
+// `kostka_match_free`
+typedef struct KostkaMatch KostkaMatch;
+
+// Numbers of dices left to roll after a take with which a player may bank
+// the turn score, 0 standing for hot dice
+typedef struct StopPoints StopPoints;
+
+// Dices and point values of a game, a plain value owned by the caller
+typedef struct KostkaRuleset {
+  // Dices rolled at the start of a turn and after hot dice, 1 to 10
+  size_t dices;
+  // Sides of every dice, 1 to `KOSTKA_MAX_SIDES`
+  uint32_t sides;
+  uint32_t one;
+  uint32_t five;
+  uint32_t straight;
+  uint32_t triple_ones;
+  // Triple of face `v` (other than ones) is worth `v * triple_face`
+  uint32_t triple_face;
+  // Points added to the turn score every time all the dices score
+  uint32_t hot_dice_bonus;
+  // Bit `n` set lets the player bank with `n` dices left to roll, bit 0
+  // after hot dice; bits above 10 are ignored
+  uint16_t stop_points;
+  // Smallest turn score the player may bank
+  uint32_t min_bank;
+  // The next player may take over the turn of a player who banked
+  bool piggyback;
+} KostkaRuleset;
+
+// Take of a hand, `dices_used[f]` counting the dices of face `f + 1`, 0
+// past the sides of the ruleset
+typedef struct KostkaTake {
+  uint32_t dices_used[KOSTKA_MAX_SIDES];
+  uint32_t value;
+} KostkaTake;
+
+// What happened on a `kostka_match_step`, fields not used by the kind are 0
+typedef struct KostkaStep {
+  enum KostkaStepKind kind;
+  // Player on turn, the winner once finished
+  size_t player;
+  // Rolled dice values of `PickTake`, the first `dices_len` are used
+  int32_t dices[KOSTKA_MAX_DICES];
+  size_t dices_len;
+  // Number of takes `kostka_match_takes` returns for `PickTake`
+  size_t takes_len;
+  // Points of the turn not banked yet, or offered by `TakeOver`
+  uint32_t turn_score;
+  size_t dices_available;
+  // Times all the dices scored this turn, counted by `HotDice`
+  uint32_t streak;
+  // How the turn of `TurnEnded` ended
+  enum KostkaTurnResult result;
+  // Points banked by `TurnEnded`
+  uint32_t value;
+  // Winning team of `TeamFinished`
+  size_t team;
+} KostkaStep;
+
+
+
+// Static description of the status `code`, a generic one for codes of no
+// status; must not be freed
+const char *kostka_status_message(int32_t code);
+
+// The default rules, 6 dices and the usual point values
+struct KostkaRuleset kostka_ruleset_default(void);
+
+// Checks that the dices and sides of `rules` are supported
+//
+// # Safety
+//
+// `rules` must be NULL or point to a ruleset.
+enum KostkaStatus kostka_ruleset_validate(const struct KostkaRuleset *rules);
+
+// Writes the takes of the `dices_len` dice values at `dices` to `takes`,
+// best first, and their number to `count`.
+//
+// `rules` may be NULL for the default rules. Pass a `takes` buffer of
+// `capacity` takes, or NULL and 0 to only ask for the count.
+//
+// # Safety
+//
+// `rules` must be NULL or point to a ruleset, `dices` must be valid for
+// `dices_len` reads, `takes` for `capacity` writes and `count` for one.
+enum KostkaStatus kostka_hand_takes(const struct KostkaRuleset *rules,
+                                    const int32_t *dices,
+                                    size_t dices_len,
+                                    struct KostkaTake *takes,
+                                    size_t capacity,
+                                    size_t *count);
+
+// Creates a match of `players` to `goal` points rolling from `seed`, and
+// writes it to `game`. The caller owns the match and releases it with
+// `kostka_match_free`.
+//
+// `rules` may be NULL for the default rules.
+//
+// # Safety
+//
+// `rules` must be NULL or point to a ruleset and `game` must be valid for
+// one write.
+enum KostkaStatus kostka_match_new(const struct KostkaRuleset *rules,
+                                   size_t players,
+                                   uint32_t goal,
+                                   uint64_t seed,
+                                   struct KostkaMatch **game);
+
+// Splits the players of a match not started yet into `teams` of two or more
+// players sharing their scores, player `i` playing for team `i % teams`.
+// The match then ends with `TeamFinished` and no player joins afterwards.
+//
+// # Safety
+//
+// `game` must be a live match.
+enum KostkaStatus kostka_match_set_teams(struct KostkaMatch *game, size_t teams);
+
+// Releases a match of `kostka_match_new`, NULL is ignored
+//
+// # Safety
+//
+// `game` must be NULL or a match not released yet.
+void kostka_match_free(struct KostkaMatch *game);
+
+// Advances the match to the next event, or writes the question the player
+// on turn has not answered yet, to `step`
+//
+// # Safety
+//
+// `game` must be a live match and `step` must be valid for one write.
+enum KostkaStatus kostka_match_step(struct KostkaMatch *game, struct KostkaStep *step);
+
+// Writes the takes of the asked `PickTake`, best first, see
+// `kostka_hand_takes` for the buffer
+//
+// # Safety
+//
+// `game` must be a live match, `takes` must be valid for `capacity` writes
+// and `count` for one.
+enum KostkaStatus kostka_match_takes(const struct KostkaMatch *game,
+                                     struct KostkaTake *takes,
+                                     size_t capacity,
+                                     size_t *count);
+
+// Answers `PickTake` with the take at `index` of `kostka_match_takes`
+//
+// # Safety
+//
+// `game` must be a live match.
+enum KostkaStatus kostka_match_take(struct KostkaMatch *game, size_t index);
+
+// Answers `PickTake` by giving up the turn, the only answer to a bust
+//
+// # Safety
+//
+// `game` must be a live match.
+enum KostkaStatus kostka_match_give_up(struct KostkaMatch *game);
+
+// Answers `ContinueOrStop`, a nonzero `stop` banks the turn score
+//
+// # Safety
+//
+// `game` must be a live match.
+enum KostkaStatus kostka_match_decide(struct KostkaMatch *game, bool stop);
+
+// Answers `TakeOver`, a nonzero `take_over` starts from the offered turn
+//
+// # Safety
+//
+// `game` must be a live match.
+enum KostkaStatus kostka_match_take_over(struct KostkaMatch *game, bool take_over);
+
+// Takes `player` out of the match, ending the turn if they are on it
+//
+// # Safety
+//
+// `game` must be a live match.
+enum KostkaStatus kostka_match_forfeit(struct KostkaMatch *game, size_t player);
+
+// Writes the banked score of every player to `scores`, see
+// `kostka_hand_takes` for the buffer
+//
+// # Safety
+//
+// `game` must be a live match, `scores` must be valid for `capacity`
+// writes and `count` for one.
+enum KostkaStatus kostka_match_scores(const struct KostkaMatch *game,
+                                      uint32_t *scores,
+                                      size_t capacity,
+                                      size_t *count);
+
+// Writes the shared score of every team to `scores`, one per player
+// without teams, see `kostka_hand_takes` for the buffer
+//
+// # Safety
+//
+// `game` must be a live match, `scores` must be valid for `capacity`
+// writes and `count` for one.
+enum KostkaStatus kostka_match_team_scores(const struct KostkaMatch *game,
+                                           uint32_t *scores,
+                                           size_t capacity,
+                                           size_t *count);
+
+#endif  /* KOSTKA_H */
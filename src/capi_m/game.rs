@@ -0,0 +1,422 @@
+use super::{
+    hand::{read_rules, takes_of, KostkaRuleset, KostkaTake},
+    status::{copy_out, guard, KostkaStatus},
+};
+use crate::{
+    dice_m::{Hand, TakeOption, MAX_DICES},
+    game_m::{GameAction, Match, MatchResult, Step, TurnResult},
+};
+
+/// Most dices of any ruleset, the length of the dices of a step
+pub const KOSTKA_MAX_DICES: usize = 10;
+
+// spelled out for the header
+const _: () = assert!(KOSTKA_MAX_DICES == MAX_DICES);
+
+/// Match stepped from C, created by `kostka_match_new` and released by
+/// `kostka_match_free`
+pub struct KostkaMatch {
+    game: Match,
+    /// Hand of the unanswered `KOSTKA_STEP_KIND_PICK_TAKE`
+    asked: Option<Hand>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KostkaStepKind {
+    /// The player starts a turn
+    TurnStarted,
+    /// The player picks a take, answer with `kostka_match_take` or
+    /// `kostka_match_give_up`
+    PickTake,
+    /// The player may bank the turn score, answer with `kostka_match_decide`
+    ContinueOrStop,
+    /// All the dices scored, the player rolls all of them again
+    HotDice,
+    TurnEnded,
+    /// The match is over, `player` won
+    Finished,
+    /// The player may start from the `turn_score` and `dices_available` the
+    /// previous player banked with, answer with `kostka_match_take_over`
+    TakeOver,
+    /// The match played in teams is over, `team` won
+    TeamFinished,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KostkaTurnResult {
+    Nothing,
+    /// Player left the game
+    Forfeit,
+    /// Player banked `value` points
+    Value,
+}
+
+/// What happened on a `kostka_match_step`, fields not used by the kind are 0
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KostkaStep {
+    pub kind: KostkaStepKind,
+    /// Player on turn, the winner once finished
+    pub player: usize,
+    /// Rolled dice values of `PickTake`, the first `dices_len` are used
+    pub dices: [i32; KOSTKA_MAX_DICES],
+    pub dices_len: usize,
+    /// Number of takes `kostka_match_takes` returns for `PickTake`
+    pub takes_len: usize,
+    /// Points of the turn not banked yet, or offered by `TakeOver`
+    pub turn_score: u32,
+    pub dices_available: usize,
+    /// Times all the dices scored this turn, counted by `HotDice`
+    pub streak: u32,
+    /// How the turn of `TurnEnded` ended
+    pub result: KostkaTurnResult,
+    /// Points banked by `TurnEnded`
+    pub value: u32,
+    /// Winning team of `TeamFinished`
+    pub team: usize,
+}
+
+impl KostkaStep {
+    fn new(kind: KostkaStepKind, player: usize) -> Self {
+        KostkaStep {
+            kind,
+            player,
+            dices: [0; KOSTKA_MAX_DICES],
+            dices_len: 0,
+            takes_len: 0,
+            turn_score: 0,
+            dices_available: 0,
+            streak: 0,
+            result: KostkaTurnResult::Nothing,
+            value: 0,
+            team: 0,
+        }
+    }
+}
+
+/// Creates a match of `players` to `goal` points rolling from `seed`, and
+/// writes it to `game`. The caller owns the match and releases it with
+/// `kostka_match_free`.
+///
+/// `rules` may be NULL for the default rules.
+///
+/// # Safety
+///
+/// `rules` must be NULL or point to a ruleset and `game` must be valid for
+/// one write.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_new(
+    rules: *const KostkaRuleset,
+    players: usize,
+    goal: u32,
+    seed: u64,
+    game: *mut *mut KostkaMatch,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        if game.is_null() {
+            return KostkaStatus::NullPointer;
+        }
+        let rules = match unsafe { read_rules(rules) } {
+            Ok(rules) => rules,
+            Err(status) => return status,
+        };
+        let mut created = Match::with_seed(seed);
+        if let Err(e) = created.set_rules(rules) {
+            return e.into();
+        }
+        created.set_limit(goal);
+        for _ in 0..players {
+            if let Err(e) = created.add_player() {
+                return e.into();
+            }
+        }
+        let created = Box::new(KostkaMatch {
+            game: created,
+            asked: None,
+        });
+        unsafe { *game = Box::into_raw(created) };
+        KostkaStatus::Ok
+    })
+}
+
+/// Splits the players of a match not started yet into `teams` of two or more
+/// players sharing their scores, player `i` playing for team `i % teams`.
+/// The match then ends with `TeamFinished` and no player joins afterwards.
+///
+/// # Safety
+///
+/// `game` must be a live match.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_set_teams(
+    game: *mut KostkaMatch,
+    teams: usize,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_mut() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        game.game.set_teams(teams).into()
+    })
+}
+
+/// Releases a match of `kostka_match_new`, NULL is ignored
+///
+/// # Safety
+///
+/// `game` must be NULL or a match not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_free(game: *mut KostkaMatch) {
+    guard((), || {
+        if !game.is_null() {
+            drop(unsafe { Box::from_raw(game) });
+        }
+    })
+}
+
+/// Advances the match to the next event, or writes the question the player
+/// on turn has not answered yet, to `step`
+///
+/// # Safety
+///
+/// `game` must be a live match and `step` must be valid for one write.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_step(
+    game: *mut KostkaMatch,
+    step: *mut KostkaStep,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let (Some(game), false) = (unsafe { game.as_mut() }, step.is_null()) else {
+            return KostkaStatus::NullPointer;
+        };
+        let player = game.game.state().my_index();
+        let next = match game.game.step() {
+            Ok(next) => next,
+            Err(e) => return e.into(),
+        };
+        let written = match next {
+            Step::TurnStarted(player) => KostkaStep::new(KostkaStepKind::TurnStarted, player),
+            Step::PickTake(hand) => {
+                let mut written = KostkaStep::new(KostkaStepKind::PickTake, player);
+                for (value, dice) in written.dices.iter_mut().zip(hand.get_dices()) {
+                    *value = dice.value;
+                }
+                written.dices_len = hand.get_dices().len();
+                written.takes_len = hand.takes().len();
+                game.asked = Some(hand);
+                written
+            }
+            Step::TakeOver => {
+                let mut written = KostkaStep::new(KostkaStepKind::TakeOver, player);
+                if let Some((score, dices)) = game.game.state().previous_turn() {
+                    written.turn_score = score;
+                    written.dices_available = dices;
+                }
+                written
+            }
+            Step::ContinueOrStop => {
+                let mut written = KostkaStep::new(KostkaStepKind::ContinueOrStop, player);
+                written.turn_score = game.game.state().turn_score();
+                written.dices_available = game.game.state().dices_available();
+                written
+            }
+            Step::HotDice(streak) => {
+                let mut written = KostkaStep::new(KostkaStepKind::HotDice, player);
+                written.streak = streak;
+                written
+            }
+            Step::TurnEnded(player, result) => {
+                let mut written = KostkaStep::new(KostkaStepKind::TurnEnded, player);
+                (written.result, written.value) = match result {
+                    TurnResult::Nothing => (KostkaTurnResult::Nothing, 0),
+                    TurnResult::Forfeit => (KostkaTurnResult::Forfeit, 0),
+                    TurnResult::Value(value) => (KostkaTurnResult::Value, value),
+                };
+                written
+            }
+            Step::Finished(MatchResult::Won(winner)) => {
+                KostkaStep::new(KostkaStepKind::Finished, winner as usize)
+            }
+            Step::Finished(MatchResult::TeamWon(team)) => {
+                let mut written = KostkaStep::new(KostkaStepKind::TeamFinished, player);
+                written.team = team as usize;
+                written
+            }
+        };
+        unsafe { *step = written };
+        KostkaStatus::Ok
+    })
+}
+
+/// Writes the takes of the asked `PickTake`, best first, see
+/// `kostka_hand_takes` for the buffer
+///
+/// # Safety
+///
+/// `game` must be a live match, `takes` must be valid for `capacity` writes
+/// and `count` for one.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_takes(
+    game: *const KostkaMatch,
+    takes: *mut KostkaTake,
+    capacity: usize,
+    count: *mut usize,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_ref() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        match &game.asked {
+            Some(hand) => unsafe { copy_out(&takes_of(hand), takes, capacity, count) },
+            None => KostkaStatus::NotAsked,
+        }
+    })
+}
+
+/// Answers `PickTake` with the take at `index` of `kostka_match_takes`
+///
+/// # Safety
+///
+/// `game` must be a live match.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_take(game: *mut KostkaMatch, index: usize) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_mut() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        let Some(hand) = &game.asked else {
+            return KostkaStatus::NotAsked;
+        };
+        let Some(&take) = hand.takes().get(index) else {
+            return KostkaStatus::InvalidTake;
+        };
+        answer_pick(game, Some(take))
+    })
+}
+
+/// Answers `PickTake` by giving up the turn, the only answer to a bust
+///
+/// # Safety
+///
+/// `game` must be a live match.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_give_up(game: *mut KostkaMatch) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_mut() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        answer_pick(game, None)
+    })
+}
+
+fn answer_pick(game: &mut KostkaMatch, take: Option<TakeOption>) -> KostkaStatus {
+    let status = KostkaStatus::from(game.game.take(take));
+    if status == KostkaStatus::Ok {
+        game.asked = None;
+    }
+    status
+}
+
+/// Answers `ContinueOrStop`, a nonzero `stop` banks the turn score
+///
+/// # Safety
+///
+/// `game` must be a live match.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_decide(game: *mut KostkaMatch, stop: bool) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_mut() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        let action = match stop {
+            true => GameAction::Stop,
+            false => GameAction::Continue,
+        };
+        game.game.decide(action).into()
+    })
+}
+
+/// Answers `TakeOver`, a nonzero `take_over` starts from the offered turn
+///
+/// # Safety
+///
+/// `game` must be a live match.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_take_over(
+    game: *mut KostkaMatch,
+    take_over: bool,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_mut() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        game.game.take_over(take_over).into()
+    })
+}
+
+/// Takes `player` out of the match, ending the turn if they are on it
+///
+/// # Safety
+///
+/// `game` must be a live match.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_forfeit(
+    game: *mut KostkaMatch,
+    player: usize,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_mut() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        let status = KostkaStatus::from(game.game.forfeit(player));
+        if player == game.game.state().my_index() {
+            game.asked = None;
+        }
+        status
+    })
+}
+
+/// Writes the banked score of every player to `scores`, see
+/// `kostka_hand_takes` for the buffer
+///
+/// # Safety
+///
+/// `game` must be a live match, `scores` must be valid for `capacity`
+/// writes and `count` for one.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_scores(
+    game: *const KostkaMatch,
+    scores: *mut u32,
+    capacity: usize,
+    count: *mut usize,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_ref() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        unsafe { copy_out(game.game.state().scores(), scores, capacity, count) }
+    })
+}
+
+/// Writes the shared score of every team to `scores`, one per player
+/// without teams, see `kostka_hand_takes` for the buffer
+///
+/// # Safety
+///
+/// `game` must be a live match, `scores` must be valid for `capacity`
+/// writes and `count` for one.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_match_team_scores(
+    game: *const KostkaMatch,
+    scores: *mut u32,
+    capacity: usize,
+    count: *mut usize,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let Some(game) = (unsafe { game.as_ref() }) else {
+            return KostkaStatus::NullPointer;
+        };
+        unsafe { copy_out(&game.game.state().team_scores(), scores, capacity, count) }
+    })
+}
@@ -0,0 +1,167 @@
+use super::status::{copy_out, guard, KostkaStatus};
+use crate::dice_m::{Dices, Hand, Ruleset, StopPoints, TakeOption, MAX_SIDES};
+
+/// Dices and point values of a game, a plain value owned by the caller
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KostkaRuleset {
+    /// Dices rolled at the start of a turn and after hot dice, 1 to 10
+    pub dices: usize,
+    /// Sides of every dice, 1 to `KOSTKA_MAX_SIDES`
+    pub sides: u32,
+    pub one: u32,
+    pub five: u32,
+    pub straight: u32,
+    pub triple_ones: u32,
+    /// Triple of face `v` (other than ones) is worth `v * triple_face`
+    pub triple_face: u32,
+    /// Points added to the turn score every time all the dices score
+    pub hot_dice_bonus: u32,
+    /// Bit `n` set lets the player bank with `n` dices left to roll, bit 0
+    /// after hot dice; bits above 10 are ignored
+    pub stop_points: u16,
+    /// Smallest turn score the player may bank
+    pub min_bank: u32,
+    /// The next player may take over the turn of a player who banked
+    pub piggyback: bool,
+}
+
+impl From<Ruleset> for KostkaRuleset {
+    fn from(rules: Ruleset) -> Self {
+        KostkaRuleset {
+            dices: rules.dices,
+            sides: rules.sides,
+            one: rules.one,
+            five: rules.five,
+            straight: rules.straight,
+            triple_ones: rules.triple_ones,
+            triple_face: rules.triple_face,
+            hot_dice_bonus: rules.hot_dice_bonus,
+            stop_points: rules.stop_points.bits(),
+            min_bank: rules.min_bank,
+            piggyback: rules.piggyback,
+        }
+    }
+}
+
+impl From<KostkaRuleset> for Ruleset {
+    fn from(rules: KostkaRuleset) -> Self {
+        Ruleset {
+            dices: rules.dices,
+            sides: rules.sides,
+            one: rules.one,
+            five: rules.five,
+            straight: rules.straight,
+            triple_ones: rules.triple_ones,
+            triple_face: rules.triple_face,
+            hot_dice_bonus: rules.hot_dice_bonus,
+            stop_points: StopPoints::from_bits(rules.stop_points),
+            min_bank: rules.min_bank,
+            piggyback: rules.piggyback,
+        }
+    }
+}
+
+/// Validated rules behind `rules`, the default rules for NULL
+///
+/// # Safety
+///
+/// `rules` must be NULL or point to a ruleset.
+pub(super) unsafe fn read_rules(rules: *const KostkaRuleset) -> Result<Ruleset, KostkaStatus> {
+    let rules = match unsafe { rules.as_ref() } {
+        Some(&rules) => Ruleset::from(rules),
+        None => Ruleset::default(),
+    };
+    rules.validate().map_err(|_| KostkaStatus::InvalidRuleset)?;
+    Ok(rules)
+}
+
+/// Most sides of any ruleset, the length of the dices used of a take
+pub const KOSTKA_MAX_SIDES: usize = 12;
+
+// spelled out for the header
+const _: () = assert!(KOSTKA_MAX_SIDES == MAX_SIDES as usize);
+
+/// Take of a hand, `dices_used[f]` counting the dices of face `f + 1`, 0
+/// past the sides of the ruleset
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KostkaTake {
+    pub dices_used: [u32; KOSTKA_MAX_SIDES],
+    pub value: u32,
+}
+
+impl From<TakeOption> for KostkaTake {
+    fn from(take: TakeOption) -> Self {
+        let mut dices_used = [0; KOSTKA_MAX_SIDES];
+        dices_used[..take.dices_used.len()].copy_from_slice(&take.dices_used);
+        KostkaTake {
+            dices_used,
+            value: take.value,
+        }
+    }
+}
+
+pub(super) fn takes_of(hand: &Hand) -> Vec<KostkaTake> {
+    hand.takes().iter().map(|&take| take.into()).collect()
+}
+
+/// The default rules, 6 dices and the usual point values
+#[no_mangle]
+pub extern "C" fn kostka_ruleset_default() -> KostkaRuleset {
+    Ruleset::default().into()
+}
+
+/// Checks that the dices and sides of `rules` are supported
+///
+/// # Safety
+///
+/// `rules` must be NULL or point to a ruleset.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_ruleset_validate(rules: *const KostkaRuleset) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        if rules.is_null() {
+            return KostkaStatus::NullPointer;
+        }
+        match unsafe { read_rules(rules) } {
+            Ok(_) => KostkaStatus::Ok,
+            Err(status) => status,
+        }
+    })
+}
+
+/// Writes the takes of the `dices_len` dice values at `dices` to `takes`,
+/// best first, and their number to `count`.
+///
+/// `rules` may be NULL for the default rules. Pass a `takes` buffer of
+/// `capacity` takes, or NULL and 0 to only ask for the count.
+///
+/// # Safety
+///
+/// `rules` must be NULL or point to a ruleset, `dices` must be valid for
+/// `dices_len` reads, `takes` for `capacity` writes and `count` for one.
+#[no_mangle]
+pub unsafe extern "C" fn kostka_hand_takes(
+    rules: *const KostkaRuleset,
+    dices: *const i32,
+    dices_len: usize,
+    takes: *mut KostkaTake,
+    capacity: usize,
+    count: *mut usize,
+) -> KostkaStatus {
+    guard(KostkaStatus::Failed, || {
+        let rules = match unsafe { read_rules(rules) } {
+            Ok(rules) => rules,
+            Err(status) => return status,
+        };
+        let dices = match (dices.is_null(), dices_len) {
+            (_, 0) => &[][..],
+            (true, _) => return KostkaStatus::NullPointer,
+            (false, len) => unsafe { std::slice::from_raw_parts(dices, len) },
+        };
+        match Hand::from_dices_with_rules(Dices::from(dices), &rules) {
+            Ok(hand) => unsafe { copy_out(&takes_of(&hand), takes, capacity, count) },
+            Err(_) => KostkaStatus::InvalidDice,
+        }
+    })
+}
@@ -0,0 +1,16 @@
+mod status;
+pub use status::{kostka_status_message, KostkaStatus};
+
+mod hand;
+pub use hand::{
+    kostka_hand_takes, kostka_ruleset_default, kostka_ruleset_validate, KostkaRuleset, KostkaTake,
+    KOSTKA_MAX_SIDES,
+};
+
+mod game;
+pub use game::{
+    kostka_match_decide, kostka_match_forfeit, kostka_match_free, kostka_match_give_up,
+    kostka_match_new, kostka_match_scores, kostka_match_step, kostka_match_take,
+    kostka_match_takes, KostkaMatch, KostkaStep, KostkaStepKind, KostkaTurnResult,
+    KOSTKA_MAX_DICES,
+};
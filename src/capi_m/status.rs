@@ -0,0 +1,167 @@
+use std::{
+    ffi::c_char,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::KostkaError;
+
+/// Result of every call of the C API.
+///
+/// Outputs are written only on `KOSTKA_STATUS_OK`, except for the count of a
+/// buffer that was too small.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KostkaStatus {
+    Ok = 0,
+    /// A required pointer was NULL
+    NullPointer = 1,
+    /// Dices or sides outside of the supported limits
+    InvalidRuleset = 2,
+    /// A dice value the dices of the ruleset do not have
+    InvalidDice = 3,
+    /// The take is not offered by the hand
+    InvalidTake = 4,
+    /// Answered a question the match did not ask
+    NotAsked = 5,
+    /// No player sits at the index
+    PlayerIndex = 6,
+    /// The buffer cannot hold every item, the count tells how many there are
+    BufferTooSmall = 7,
+    /// Every player forfeited, nobody can win
+    AllForfeited = 8,
+    /// Any other failure of the engine, panics included
+    Failed = 9,
+    /// The players cannot be split into teams of the same size
+    InvalidTeams = 10,
+}
+
+impl From<KostkaError> for KostkaStatus {
+    fn from(e: KostkaError) -> Self {
+        match e {
+            KostkaError::InvalidTake(_) => KostkaStatus::InvalidTake,
+            KostkaError::InvalidDice(_) => KostkaStatus::InvalidDice,
+            KostkaError::PlayerIndex(_) => KostkaStatus::PlayerIndex,
+            KostkaError::AllForfeited => KostkaStatus::AllForfeited,
+            KostkaError::Ruleset(_) => KostkaStatus::InvalidRuleset,
+            KostkaError::NotAsked => KostkaStatus::NotAsked,
+            KostkaError::Teams(_) => KostkaStatus::InvalidTeams,
+            // players, sockets and servers are not reachable from C
+            KostkaError::UnknownPlayer(_)
+            | KostkaError::Io(_)
+            | KostkaError::Refused(_)
+            | KostkaError::UnknownGame(_) => KostkaStatus::Failed,
+        }
+    }
+}
+
+impl From<Result<(), KostkaError>> for KostkaStatus {
+    fn from(result: Result<(), KostkaError>) -> Self {
+        match result {
+            Ok(()) => KostkaStatus::Ok,
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl KostkaStatus {
+    const ALL: [KostkaStatus; 11] = [
+        KostkaStatus::Ok,
+        KostkaStatus::NullPointer,
+        KostkaStatus::InvalidRuleset,
+        KostkaStatus::InvalidDice,
+        KostkaStatus::InvalidTake,
+        KostkaStatus::NotAsked,
+        KostkaStatus::PlayerIndex,
+        KostkaStatus::BufferTooSmall,
+        KostkaStatus::AllForfeited,
+        KostkaStatus::Failed,
+        KostkaStatus::InvalidTeams,
+    ];
+
+    /// Status of a code coming from C, None for codes of no status
+    fn from_code(code: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|&status| status as i32 == code)
+    }
+}
+
+/// Runs the body of an entry point, returning `failed` when it panics
+/// instead of unwinding into C
+pub(super) fn guard<T>(failed: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(failed)
+}
+
+/// Static description of the status `code`, a generic one for codes of no
+/// status; must not be freed
+#[no_mangle]
+pub extern "C" fn kostka_status_message(code: i32) -> *const c_char {
+    let Some(status) = KostkaStatus::from_code(code) else {
+        return c"unknown status".as_ptr();
+    };
+    let message = match status {
+        KostkaStatus::Ok => c"ok",
+        KostkaStatus::NullPointer => c"a required pointer was NULL",
+        KostkaStatus::InvalidRuleset => c"dices or sides outside of the supported limits",
+        KostkaStatus::InvalidDice => c"dice value out of range",
+        KostkaStatus::InvalidTake => c"take is not offered by the hand",
+        KostkaStatus::NotAsked => c"answered a question that was not asked",
+        KostkaStatus::PlayerIndex => c"no player at the index",
+        KostkaStatus::BufferTooSmall => c"buffer too small",
+        KostkaStatus::AllForfeited => c"all players forfeited",
+        KostkaStatus::Failed => c"engine failure",
+        KostkaStatus::InvalidTeams => c"players cannot be split into the teams",
+    };
+    message.as_ptr()
+}
+
+/// Copies `items` to the `capacity` long buffer `out` and their number to
+/// `count`, `out` may be NULL when `capacity` is 0 to only ask for the count.
+///
+/// # Safety
+///
+/// `out` must be valid for `capacity` writes and `count` for one.
+pub(super) unsafe fn copy_out<T: Copy>(
+    items: &[T],
+    out: *mut T,
+    capacity: usize,
+    count: *mut usize,
+) -> KostkaStatus {
+    if count.is_null() || (out.is_null() && capacity > 0) {
+        return KostkaStatus::NullPointer;
+    }
+    unsafe { *count = items.len() };
+    if items.len() > capacity {
+        return KostkaStatus::BufferTooSmall;
+    }
+    if !items.is_empty() {
+        unsafe { std::ptr::copy_nonoverlapping(items.as_ptr(), out, items.len()) };
+    }
+    KostkaStatus::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_fail() {
+        assert_eq!(
+            guard(KostkaStatus::Failed, || panic!("boom")),
+            KostkaStatus::Failed
+        );
+        assert_eq!(
+            guard(KostkaStatus::Failed, || KostkaStatus::Ok),
+            KostkaStatus::Ok
+        );
+    }
+
+    #[test]
+    fn every_code_round_trips() {
+        for status in KostkaStatus::ALL {
+            assert_eq!(KostkaStatus::from_code(status as i32), Some(status));
+        }
+        assert_eq!(
+            KostkaStatus::from_code(KostkaStatus::ALL.len() as i32),
+            None
+        );
+    }
+}
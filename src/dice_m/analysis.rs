@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use super::{Dices, Hand, Ruleset, TakeTable};
+use crate::KostkaError;
+
+/// Outcome statistics of a single roll of `dices` dice.
+///
+/// Counts are over all `sides^dices` equally likely rolls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceStats {
+    pub dices: usize,
+    pub outcomes: u64,
+    /// Rolls with no scoring take
+    pub busts: u64,
+    /// Rolls where some take uses every dice
+    pub hot_dices: u64,
+    /// Value of the best take -> number of rolls, busts excluded
+    pub best_takes: BTreeMap<u32, u64>,
+}
+
+impl DiceStats {
+    pub fn bust_chance(&self) -> f64 {
+        self.busts as f64 / self.outcomes as f64
+    }
+
+    pub fn hot_dice_chance(&self) -> f64 {
+        self.hot_dices as f64 / self.outcomes as f64
+    }
+
+    pub fn best_take_chance(&self, value: u32) -> f64 {
+        let n = self.best_takes.get(&value).copied().unwrap_or(0);
+        n as f64 / self.outcomes as f64
+    }
+
+    /// Expected value of the best take, counting busts as 0
+    pub fn expected_best(&self) -> f64 {
+        let total: u64 = self
+            .best_takes
+            .iter()
+            .map(|(&value, &n)| value as u64 * n)
+            .sum();
+        total as f64 / self.outcomes as f64
+    }
+}
+
+/// Enumerates every roll of `dices` dice and scores it under `rules`.
+/// Errs when `rules` are not supported.
+pub fn analyze(dices: usize, rules: &Ruleset) -> Result<DiceStats, KostkaError> {
+    let mut stats = DiceStats {
+        dices,
+        outcomes: 0,
+        busts: 0,
+        hot_dices: 0,
+        best_takes: BTreeMap::new(),
+    };
+
+    let table = TakeTable::for_rules(rules)?;
+    for (roll, n) in distinct_rolls(dices, rules.sides) {
+        let hand = Hand::from_dices_in(roll, &table);
+
+        stats.outcomes += n;
+        match hand.get_takes().map(|take| take.value()).max() {
+            Some(value) => *stats.best_takes.entry(value).or_insert(0) += n,
+            None => stats.busts += n,
+        }
+        if !hand.takes_use_all().is_empty() {
+            stats.hot_dices += n;
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Every distinct roll of `dices` dice with `sides` sides, each with the
+/// number of the `sides^dices` ordered rolls that give it
+pub fn distinct_rolls(dices: usize, sides: u32) -> Vec<(Dices, u64)> {
+    let mut rolls = Vec::new();
+    push_rolls(dices, 1, sides, &mut Vec::with_capacity(dices), &mut rolls);
+    rolls
+}
+
+/// Statistics for 1 to `rules.dices` dice
+pub fn analyze_all(rules: &Ruleset) -> Result<Vec<DiceStats>, KostkaError> {
+    (1..=rules.dices).map(|n| analyze(n, rules)).collect()
+}
+
+/// Extends sorted `faces` by `left` dices showing `face` or more, in
+/// ascending order of the rolls
+fn push_rolls(
+    left: usize,
+    face: u32,
+    sides: u32,
+    faces: &mut Vec<i32>,
+    rolls: &mut Vec<(Dices, u64)>,
+) {
+    if left == 0 {
+        rolls.push((Dices::from(faces.as_slice()), orderings(faces)));
+        return;
+    }
+    if face > sides {
+        return;
+    }
+    for n in (0..=left).rev() {
+        faces.extend(std::iter::repeat_n(face as i32, n));
+        push_rolls(left - n, face + 1, sides, faces, rolls);
+        faces.truncate(faces.len() - n);
+    }
+}
+
+/// Number of orderings of sorted `faces`
+fn orderings(faces: &[i32]) -> u64 {
+    let factorial = |n: usize| (1..=n as u64).product::<u64>();
+    let repeats: u64 = faces
+        .chunk_by(|a, b| a == b)
+        .map(|same| factorial(same.len()))
+        .product();
+    factorial(faces.len()) / repeats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_dice() {
+        let stats = analyze(1, &Ruleset::default()).unwrap();
+
+        assert_eq!(stats.outcomes, 6);
+        assert_eq!(stats.busts, 4);
+        assert_eq!(stats.hot_dices, 2);
+        assert_eq!(stats.best_takes, BTreeMap::from([(50, 1), (100, 1)]));
+        assert_eq!(stats.expected_best(), 25.0);
+    }
+
+    #[test]
+    fn two_dices() {
+        let stats = analyze(2, &Ruleset::default()).unwrap();
+
+        assert_eq!(stats.outcomes, 36);
+        assert_eq!(stats.busts, 16); // both dice from {2, 3, 4, 6}
+        assert_eq!(stats.hot_dices, 4); // both dice from {1, 5}
+    }
+
+    #[test]
+    fn six_dices() {
+        let stats = analyze(6, &Ruleset::default()).unwrap();
+
+        assert_eq!(stats.outcomes, 46656);
+        // no one, no five and at most a pair of 2, 3, 4 and 6
+        assert_eq!(stats.busts, 1440);
+        assert!(stats.best_take_chance(8000) > 0.0); // six ones
+    }
+
+    #[test]
+    fn distinct_rolls_cover_all() {
+        let rolls = distinct_rolls(6, 6);
+
+        assert_eq!(rolls.len(), 462);
+        assert_eq!(rolls.iter().map(|(_, n)| n).sum::<u64>(), 46656);
+        assert_eq!(rolls[0], (Dices::from([1; 6]), 1));
+    }
+
+    #[test]
+    fn fewer_sides() {
+        let rolls = distinct_rolls(3, 4);
+
+        assert_eq!(rolls.len(), 20);
+        assert_eq!(rolls.iter().map(|(_, n)| n).sum::<u64>(), 64);
+
+        let rules = Ruleset {
+            sides: 4,
+            ..Ruleset::default()
+        };
+        let stats = analyze(1, &rules).unwrap();
+        assert_eq!(stats.outcomes, 4);
+        assert_eq!(stats.busts, 3); // no five
+    }
+
+    #[test]
+    fn rules_change_values() {
+        let rules = Ruleset {
+            five: 75,
+            ..Ruleset::default()
+        };
+        let stats = analyze(1, &rules).unwrap();
+
+        assert_eq!(stats.best_takes, BTreeMap::from([(75, 1), (100, 1)]));
+    }
+}
@@ -1,33 +1,42 @@
 use rand::prelude::*;
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
+
+/// Sides of every dice
+const SIDES: i32 = 6;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Dice {
-    sides: i32,
     pub value: i32,
 }
 
 impl Dice {
     // Dice with 6 sides and random value
+    #[cfg(feature = "native")]
     pub fn new() -> Self {
-        let mut dice = Dice { sides: 6, value: 0 };
+        let mut dice = Dice { value: 0 };
         dice.roll();
         dice
     }
 
     pub fn from_value(value: i32) -> Self {
-        Dice { sides: 6, value }
+        Dice { value }
     }
 
-    fn roll_internal(&self) -> i32 {
-        rand::thread_rng().gen_range(1..=self.sides)
+    fn roll_internal<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+        rng.gen_range(1..=SIDES)
     }
 
+    #[cfg(feature = "native")]
     pub fn roll(&mut self) {
-        self.value = self.roll_internal();
+        self.roll_with(&mut rand::thread_rng());
+    }
+
+    pub fn roll_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.value = self.roll_internal(rng);
     }
 }
 
+#[cfg(feature = "native")]
 impl Default for Dice {
     fn default() -> Self {
         Self::new()
@@ -40,7 +49,7 @@ impl Display for Dice {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Dices(Vec<Dice>); // must be vec, 2 ones cant be represented
 
 impl Dices {
@@ -48,12 +57,23 @@ impl Dices {
         Dices(Default::default())
     }
 
+    #[cfg(feature = "native")]
     pub fn of_length(n: usize) -> Self {
+        Self::of_length_with(n, &mut rand::thread_rng())
+    }
+
+    /// Rolls `n` dice using `rng`
+    pub fn of_length_with<R: Rng + ?Sized>(n: usize, rng: &mut R) -> Self {
+        Self::of_sides_with(n, SIDES as u32, rng)
+    }
+
+    /// Rolls `n` dice with `sides` sides using `rng`
+    pub fn of_sides_with<R: Rng + ?Sized>(n: usize, sides: u32, rng: &mut R) -> Self {
         let mut v = Vec::with_capacity(n);
         for _ in 0..n {
-            v.push(Dice::default());
+            v.push(Dice::from_value(rng.gen_range(1..=sides as i32)));
         }
-        v.sort_by(|dice, other| dice.value.cmp(&other.value));
+        v.sort_by_key(|dice| dice.value);
         Dices(v)
     }
 
@@ -61,6 +81,10 @@ impl Dices {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, Dice> {
         //todo ref?
         self.0.iter()
@@ -69,6 +93,84 @@ impl Dices {
     pub fn as_slice(&self) -> &[Dice] {
         self.0.as_slice()
     }
+
+    /// Errs on the first value outside of 1..=`sides`
+    pub fn validate(&self, sides: u32) -> Result<(), DicesError> {
+        match self
+            .iter()
+            .find(|dice| !(1..=sides as i32).contains(&dice.value))
+        {
+            Some(dice) => Err(DicesError::OutOfRange(dice.value as i64)),
+            None => Ok(()),
+        }
+    }
+
+    /// Errs on more than `max` dices
+    pub fn validate_len(&self, max: usize) -> Result<(), DicesError> {
+        match self.len() {
+            n if n > max => Err(DicesError::TooMany(n)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Dice values that do not make valid dices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DicesError {
+    Empty,
+    NotANumber(String),
+    OutOfRange(i64),
+    TooMany(usize),
+}
+
+impl Display for DicesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DicesError::Empty => write!(f, "no dice values"),
+            DicesError::NotANumber(s) => write!(f, "'{}' is not a dice value", s),
+            DicesError::OutOfRange(v) => write!(f, "dice value {} is out of range", v),
+            DicesError::TooMany(n) => write!(f, "{} dices are too many for a hand", n),
+        }
+    }
+}
+
+impl std::error::Error for DicesError {}
+
+/// Parses values separated by whitespace or commas, "1 1 5 6 6 6", or one
+/// digit per dice, "115666"
+impl FromStr for Dices {
+    type Err = DicesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let separated = |c: char| c.is_whitespace() || c == ',';
+        let tokens: Vec<&str> = if s.trim().contains(separated) {
+            s.split(separated)
+                .filter(|token| !token.is_empty())
+                .collect()
+        } else {
+            let s = s.trim();
+            s.char_indices()
+                .map(|(i, c)| &s[i..i + c.len_utf8()])
+                .collect()
+        };
+        if tokens.is_empty() {
+            return Err(DicesError::Empty);
+        }
+
+        let values = tokens
+            .iter()
+            .map(|token| {
+                let value: i64 = token
+                    .parse()
+                    .map_err(|_| DicesError::NotANumber(token.to_string()))?;
+                i32::try_from(value)
+                    .ok()
+                    .filter(|v| (1..=SIDES).contains(v))
+                    .ok_or(DicesError::OutOfRange(value))
+            })
+            .collect::<Result<Vec<i32>, _>>()?;
+        Ok(Dices::from(values.as_slice()))
+    }
 }
 
 impl Default for Dices {
@@ -84,6 +186,19 @@ impl<const N: usize> From<[i32; N]> for Dices {
     }
 }
 
+impl From<&[Dice]> for Dices {
+    fn from(dices: &[Dice]) -> Self {
+        Dices(dices.to_vec())
+    }
+}
+
+impl From<&[i32]> for Dices {
+    fn from(values: &[i32]) -> Self {
+        let s = values.iter().map(|&i| Dice::from_value(i)).collect();
+        Dices(s)
+    }
+}
+
 impl Display for Dices {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
@@ -114,6 +229,37 @@ mod tests {
     mod dices {
         use super::*;
 
+        mod parse_tests {
+            use super::*;
+
+            #[test]
+            fn separated() {
+                let dices: Dices = "1 1 5 6 6 6".parse().unwrap();
+                assert_eq!(dices, Dices::from([1, 1, 5, 6, 6, 6]));
+
+                let dices: Dices = " 2,3, 4 ".parse().unwrap();
+                assert_eq!(dices, Dices::from([2, 3, 4]));
+            }
+
+            #[test]
+            fn digits() {
+                let dices: Dices = "115666".parse().unwrap();
+                assert_eq!(dices, Dices::from([1, 1, 5, 6, 6, 6]));
+            }
+
+            #[test]
+            fn errors() {
+                assert_eq!("".parse::<Dices>(), Err(DicesError::Empty));
+                assert_eq!("1 7".parse::<Dices>(), Err(DicesError::OutOfRange(7)));
+                assert_eq!("1170".parse::<Dices>(), Err(DicesError::OutOfRange(7)));
+                assert_eq!("1 -2".parse::<Dices>(), Err(DicesError::OutOfRange(-2)));
+                assert_eq!(
+                    "1 x".parse::<Dices>(),
+                    Err(DicesError::NotANumber("x".into()))
+                );
+            }
+        }
+
         mod iter_tests {
             use super::*;
 
@@ -0,0 +1,217 @@
+use std::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::MAX_SIDES;
+use crate::KostkaError;
+
+/// Dices showing every face of a dice, `counts[f]` of face `f + 1`.
+///
+/// Holds as many faces as the dice has sides, up to [`MAX_SIDES`], and
+/// derefs to their slice.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "Vec<u32>", into = "Vec<u32>")]
+pub struct FaceCounts {
+    /// Zero past `sides`
+    counts: [u32; MAX_SIDES as usize],
+    sides: u8,
+}
+
+impl FaceCounts {
+    /// No dices of any of the `sides` faces
+    pub fn new(sides: u32) -> Self {
+        assert!(sides <= MAX_SIDES, "Too many sides to count");
+        FaceCounts {
+            counts: [0; MAX_SIDES as usize],
+            sides: sides as u8,
+        }
+    }
+
+    /// Faces counted, the sides of the dice
+    pub fn sides(&self) -> u32 {
+        self.sides as u32
+    }
+
+    /// Number of dices of all the faces
+    pub fn total(&self) -> u32 {
+        self.iter().sum()
+    }
+
+    /// Counts left after setting aside the dices of `other`, None when
+    /// they are not among these
+    pub fn checked_sub(&self, other: &FaceCounts) -> Option<FaceCounts> {
+        if self.sides != other.sides {
+            return None;
+        }
+        let mut left = *self;
+        for (n, &used) in left.iter_mut().zip(other) {
+            *n = n.checked_sub(used)?;
+        }
+        Some(left)
+    }
+
+    /// Counts packed four bits per face, a cheap key of up to 15 dices of
+    /// a face; None for more
+    pub(crate) fn packed(&self) -> Option<u64> {
+        self.iter().rev().try_fold(0, |key, &n| match n {
+            0..=15 => Some(key << 4 | n as u64),
+            _ => None,
+        })
+    }
+
+    /// Dices showing `face`, 1 to the sides, none for other faces
+    pub fn of_face(&self, face: u32) -> u32 {
+        let index = face.checked_sub(1);
+        index
+            .and_then(|i| self.get(i as usize))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl Deref for FaceCounts {
+    type Target = [u32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.counts[..self.sides as usize]
+    }
+}
+
+impl DerefMut for FaceCounts {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.counts[..self.sides as usize]
+    }
+}
+
+impl Debug for FaceCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl IntoIterator for FaceCounts {
+    type Item = u32;
+    type IntoIter = std::iter::Take<std::array::IntoIter<u32, { MAX_SIDES as usize }>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter().take(self.sides as usize)
+    }
+}
+
+impl<'a> IntoIterator for &'a FaceCounts {
+    type Item = &'a u32;
+    type IntoIter = std::slice::Iter<'a, u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Counts of as many faces as the array holds
+impl<const N: usize> From<[u32; N]> for FaceCounts {
+    fn from(counts: [u32; N]) -> Self {
+        let mut face_counts = FaceCounts::new(N as u32);
+        face_counts.copy_from_slice(&counts);
+        face_counts
+    }
+}
+
+impl<const N: usize> PartialEq<[u32; N]> for FaceCounts {
+    fn eq(&self, other: &[u32; N]) -> bool {
+        **self == *other
+    }
+}
+
+/// Errs on more faces than [`MAX_SIDES`]
+impl TryFrom<Vec<u32>> for FaceCounts {
+    type Error = KostkaError;
+
+    fn try_from(counts: Vec<u32>) -> Result<Self, Self::Error> {
+        if counts.len() > MAX_SIDES as usize {
+            return Err(KostkaError::Ruleset(format!(
+                "{} faces, expected at most {}",
+                counts.len(),
+                MAX_SIDES
+            )));
+        }
+        let mut face_counts = FaceCounts::new(counts.len() as u32);
+        face_counts.copy_from_slice(&counts);
+        Ok(face_counts)
+    }
+}
+
+impl From<FaceCounts> for Vec<u32> {
+    fn from(counts: FaceCounts) -> Self {
+        counts.to_vec()
+    }
+}
+
+/// No dices of a six-sided dice
+impl Default for FaceCounts {
+    fn default() -> Self {
+        Self::new(6)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_the_faces_of_the_dice() {
+        let mut counts = FaceCounts::new(8);
+        counts[7] += 2;
+        counts[0] += 1;
+
+        assert_eq!(counts.len(), 8);
+        assert_eq!(counts, [1, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(counts.total(), 3);
+        assert_eq!((counts.of_face(8), counts.of_face(9)), (2, 0));
+        assert_eq!(format!("{:?}", counts), "[1, 0, 0, 0, 0, 0, 0, 2]");
+    }
+
+    #[test]
+    fn sets_dices_aside() {
+        let counts = FaceCounts::from([2, 0, 0, 0, 1, 0]);
+
+        assert_eq!(
+            counts.checked_sub(&FaceCounts::from([1, 0, 0, 0, 1, 0])),
+            Some(FaceCounts::from([1, 0, 0, 0, 0, 0]))
+        );
+        assert_eq!(
+            counts.checked_sub(&FaceCounts::from([3, 0, 0, 0, 0, 0])),
+            None
+        );
+        assert_eq!(counts.checked_sub(&FaceCounts::new(4)), None);
+    }
+
+    #[test]
+    fn packs_four_bits_per_face() {
+        let mut counts = FaceCounts::new(12);
+        counts[0] = 1;
+        counts[11] = 15;
+
+        assert_eq!(counts.packed(), Some(15 << 44 | 1));
+        counts[1] = 16;
+        assert_eq!(counts.packed(), None);
+    }
+
+    #[test]
+    fn sides_tell_counts_apart() {
+        assert_ne!(FaceCounts::new(4), FaceCounts::new(6));
+        assert!(FaceCounts::from([0, 1]) < FaceCounts::from([1, 0]));
+    }
+
+    #[test]
+    fn serialized_as_a_list() {
+        let counts = FaceCounts::from([1, 0, 0, 0, 2, 0]);
+        let json = serde_json::to_string(&counts).unwrap();
+
+        assert_eq!(json, "[1,0,0,0,2,0]");
+        assert_eq!(serde_json::from_str::<FaceCounts>(&json).unwrap(), counts);
+        assert!(serde_json::from_str::<FaceCounts>(&format!("{:?}", [0; 13])).is_err());
+    }
+}
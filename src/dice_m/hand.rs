@@ -1,11 +1,24 @@
-use super::{Dice, Dices, ScoredCombination, TakeOption};
-use std::{collections::HashSet, fmt::Display};
-
-#[derive(Debug)]
+use super::{
+    take_option::one_face_values, Breakdown, Dice, Dices, DicesError, FaceCounts, Ruleset,
+    ScoredCombination, TakeOption, TakeTable, MAX_DICES,
+};
+use crate::KostkaError;
+use rand::Rng;
+use std::{collections::HashSet, fmt::Display, sync::Arc};
+
+/// Rolled dices and their takes.
+///
+/// Holds up to [`MAX_DICES`] dices inline and shares the takes with the
+/// take table, so rolling one allocates nothing.
+#[derive(Debug, Clone)]
 pub struct Hand {
-    dices: Dices,
-    dice_counts: [u32; 6], //todo generic
-    take_options: HashSet<TakeOption>,
+    /// Only the first `dices_len` are in the hand, sorted when rolled
+    dices: [Dice; MAX_DICES],
+    dices_len: usize,
+    dice_counts: FaceCounts,
+    /// Best first, shared with the take table of the ruleset
+    take_options: Arc<[TakeOption]>,
+    rules: Ruleset,
 }
 
 impl Hand {
@@ -19,26 +32,96 @@ impl Hand {
     //     hand
     // }
 
+    /// Panics on more than [`MAX_DICES`] dices
+    #[cfg(feature = "native")]
     pub fn with_dices(n: usize) -> Self {
+        Self::from_dices_in(Dices::of_length(n), &TakeTable::for_default_rules())
+    }
+
+    /// Errs when `rules` are not supported
+    #[cfg(feature = "native")]
+    pub fn with_dices_and_rules(n: usize, rules: &Ruleset) -> Result<Self, KostkaError> {
+        let table = TakeTable::for_rules(rules)?;
+        Ok(Self::from_dices_in(Dices::of_length(n), &table))
+    }
+
+    /// Rolls `n` dice using `rng`, sides and takes come from `table`.
+    ///
+    /// Panics on more than [`MAX_DICES`] dices.
+    pub fn roll<R: Rng + ?Sized>(n: usize, table: &TakeTable, rng: &mut R) -> Self {
+        assert!(n <= MAX_DICES, "Too many dices to roll");
+        let sides = table.rules().sides as i32;
+        let mut dices = [Dice::from_value(0); MAX_DICES];
+        for dice in &mut dices[..n] {
+            *dice = Dice::from_value(rng.gen_range(1..=sides));
+        }
+        dices[..n].sort_unstable_by_key(|dice| dice.value);
+        Self::from_inline(dices, n, table)
+    }
+
+    /// Hand of known dices scored by the default rules
+    pub fn from_dices(dices: Dices) -> Result<Self, DicesError> {
+        let table = TakeTable::for_default_rules();
+        dices.validate(table.rules().sides)?;
+        dices.validate_len(MAX_DICES)?;
+        Ok(Self::from_dices_in(dices, &table))
+    }
+
+    /// Hand of known dices scored by `rules`, errs on unsupported `rules` and
+    /// on values their dices do not have
+    pub fn from_dices_with_rules(dices: Dices, rules: &Ruleset) -> Result<Self, KostkaError> {
+        let table = TakeTable::for_rules(rules)?;
+        dices.validate(rules.sides)?;
+        dices.validate_len(MAX_DICES)?;
+        Ok(Self::from_dices_in(dices, &table))
+    }
+
+    /// Panics on more than [`MAX_DICES`] dices
+    pub(crate) fn from_dices_in(dices: Dices, table: &TakeTable) -> Self {
+        let n = dices.len();
+        assert!(n <= MAX_DICES, "Too many dices for a hand");
+        let mut inline = [Dice::from_value(0); MAX_DICES];
+        inline[..n].copy_from_slice(dices.as_slice());
+        Self::from_inline(inline, n, table)
+    }
+
+    fn from_inline(dices: [Dice; MAX_DICES], dices_len: usize, table: &TakeTable) -> Self {
         let mut hand = Hand {
-            dices: Dices::of_length(n),
-            dice_counts: Default::default(),
-            take_options: Default::default(),
+            dices,
+            dices_len,
+            dice_counts: FaceCounts::new(table.rules().sides),
+            take_options: Arc::new([]),
+            rules: *table.rules(),
         };
-        hand.analyze_dices();
+        hand.analyze_dices(table);
         hand
     }
 
-    pub fn get_takes(&self) -> std::collections::hash_set::Iter<'_, TakeOption> {
+    pub fn get_takes(&self) -> std::slice::Iter<'_, TakeOption> {
         self.take_options.iter()
     }
 
+    /// Every take, best first by the order of [`TakeOption`]
+    pub fn takes(&self) -> &[TakeOption] {
+        &self.take_options
+    }
+
     pub fn get_dices(&self) -> &[Dice] {
-        self.dices.as_slice()
+        &self.dices[..self.dices_len]
     }
 
     pub fn dices_used(&self) -> usize {
-        self.dices.len()
+        self.dices_len
+    }
+
+    /// Most valuable take of every number of dices used, ranked.
+    ///
+    /// Any other take scores less while leaving the same dices to roll.
+    pub fn best_takes(&self) -> Vec<TakeOption> {
+        let mut seen = vec![false; self.dices_used() + 1];
+        let mut takes = self.takes().to_vec();
+        takes.retain(|take| !std::mem::replace(&mut seen[take.dices_count()], true));
+        takes
     }
 
     pub fn takes_use_all(&self) -> Vec<&TakeOption> {
@@ -49,88 +132,113 @@ impl Hand {
             .collect()
     }
 
+    /// Rules the takes are scored by
+    pub fn rules(&self) -> &Ruleset {
+        &self.rules
+    }
+
+    /// Combinations `take` is made of under the rules of the hand
+    pub fn breakdown(&self, take: &TakeOption) -> Option<Breakdown> {
+        take.breakdown(&self.rules)
+    }
+
     fn generate_counts(&mut self) {
-        self.dice_counts = Default::default(); // zero out
-        for dice in self.dices.iter() {
+        self.dice_counts = FaceCounts::new(self.rules.sides);
+        for dice in &self.dices[..self.dices_len] {
             match dice.value {
-                1..=6 => self.dice_counts[(dice.value as usize) - 1] += 1,
-                _ => panic!("Dice has value outside of 1..=6"),
+                v if (1..=self.rules.sides as i32).contains(&v) => {
+                    self.dice_counts[v as usize - 1] += 1
+                }
+                _ => unreachable!("Dice values are validated"),
             };
         }
     }
 
-    fn analyze_dices(&mut self) {
+    fn analyze_dices(&mut self, table: &TakeTable) {
         self.generate_counts();
-        self.take_options = self.generate_basic_options();
-        self.combine_options();
-    }
-
-    fn dices_with(&self, value: u32) -> u32 {
-        let dice_range = 1..=6;
-        assert!(dice_range.contains(&value));
-        self.dice_counts[(value as usize) - 1]
+        self.take_options = match table.takes(&self.dice_counts) {
+            Some(takes) => takes.clone(),
+            None => ranked_takes(enumerate_takes(&self.dice_counts, table.rules())),
+        };
     }
+}
 
-    fn generate_basic_options(&self) -> HashSet<TakeOption> {
-        let mut options = HashSet::default();
-
-        for face in 1..=6u32 {
-            let n_of_dices = self.dices_with(face);
-            options.extend(TakeOption::one_face_all_combinations(face, n_of_dices));
-        }
+/// Takes best first, each set of dices only at its most valuable split
+pub(crate) fn ranked_takes(takes: HashSet<TakeOption>) -> Arc<[TakeOption]> {
+    let mut takes: Vec<TakeOption> = takes.into_iter().collect();
+    takes.sort_unstable_by(|a, b| b.cmp(a));
+    // worse splits of the same dices rank after the best one
+    let mut seen = HashSet::new();
+    takes.retain(|take| seen.insert(take.dices_used));
+    takes.into()
+}
 
-        if self.dice_counts == [1, 1, 1, 1, 1, 1] {
-            options.insert(TakeOption::from_combination(
-                [1, 1, 1, 1, 1, 1],
-                ScoredCombination::Straight,
-            ));
+/// Every sub-multiset of the dices that scores as a whole, once for every
+/// value of its splits into combinations
+pub(crate) fn enumerate_takes(dice_counts: &FaceCounts, rules: &Ruleset) -> HashSet<TakeOption> {
+    let mut takes = HashSet::new();
+    let mut used = FaceCounts::new(dice_counts.sides());
+    let straight = ScoredCombination::Straight;
+    let straight = straight.fits(dice_counts.sides()).then(|| {
+        (
+            straight.dices_used(dice_counts.sides()),
+            straight.score_with(rules),
+        )
+    });
+
+    // values of k dices of one face, looked up for every sub-multiset
+    let face_values: Vec<Vec<Vec<u32>>> = (0..dice_counts.len())
+        .map(|face| {
+            (0..=dice_counts[face])
+                .map(|k| one_face_values(face as u32 + 1, k, rules))
+                .collect()
+        })
+        .collect();
+
+    loop {
+        // next sub-multiset, counting every face from 0 to its dice count
+        let mut face = 0;
+        loop {
+            if face == used.len() {
+                return takes;
+            }
+            if used[face] < dice_counts[face] {
+                used[face] += 1;
+                break;
+            }
+            used[face] = 0;
+            face += 1;
         }
 
-        options
-    }
-
-    fn takes_overlap(&self, first: &TakeOption, second: &TakeOption) -> bool {
-        use itertools::izip;
-
-        let available_dices = self.dice_counts.iter();
-        let first_dices = first.dices_used.iter();
-        let second_dices = second.dices_used.iter();
-
-        izip!(available_dices, first_dices, second_dices).any(|(&av, &f, &s)| av < f + s)
-    }
-
-    fn includes_take(&self, take: &TakeOption) -> bool {
-        self.take_options.iter().any(|x| x == take)
-    }
-
-    fn make_combinations(&self) -> HashSet<TakeOption> {
-        let mut new_combinations = HashSet::new();
-
-        for option in self.take_options.iter() {
-            for option_ot in self.take_options.iter() {
-                if !self.takes_overlap(option, option_ot) {
-                    let new_option = option.combine(option_ot);
-                    if !self.includes_take(&new_option) {
-                        new_combinations.insert(new_option);
-                    }
-                }
+        let mut values = split_values(&used, &face_values);
+        if let Some((straight, straight_value)) = straight {
+            if let Some(rest) = used.checked_sub(&straight) {
+                let rest_values = split_values(&rest, &face_values);
+                values.extend(rest_values.into_iter().map(|rest| straight_value + rest));
             }
         }
-
-        new_combinations
+        takes.extend(values.into_iter().map(|value| TakeOption {
+            dices_used: used,
+            value,
+        }));
     }
+}
 
-    fn combine_options(&mut self) {
-        loop {
-            let new_options = self.make_combinations();
-            match new_options.len() {
-                0 => break,
-                _ => {
-                    self.take_options.extend(new_options);
-                }
-            }
+/// Values of the splits of `used` into combinations of one face each
+fn split_values(used: &FaceCounts, face_values: &[Vec<Vec<u32>>]) -> Vec<u32> {
+    let mut sums = vec![0];
+    for (face, &n) in used.iter().enumerate() {
+        let values = &face_values[face][n as usize];
+        if values != &[0] {
+            sums = sums
+                .iter()
+                .flat_map(|sum| values.iter().map(move |value| sum + value))
+                .collect();
+            sums.sort_unstable();
+            sums.dedup();
         }
     }
+    sums
 }
 
 impl Display for Hand {
@@ -138,7 +246,8 @@ impl Display for Hand {
         write!(
             f,
             " Dices: {}\nCounts: {:?}\n Takes:\n",
-            self.dices, self.dice_counts
+            Dices::from(self.get_dices()),
+            self.dice_counts
         )?;
         for opt in self.take_options.iter() {
             writeln!(f, "{:?}", opt)?;
@@ -151,15 +260,129 @@ impl Display for Hand {
 mod tests {
 
     use super::*;
+    use std::collections::HashMap;
 
     fn hand_from_dices(dices: Dices) -> Hand {
-        let mut hand = Hand {
-            dices,
-            dice_counts: Default::default(),
-            take_options: Default::default(),
+        Hand::from_dices(dices).unwrap()
+    }
+
+    fn takes_set(hand: &Hand) -> HashSet<TakeOption> {
+        hand.get_takes().copied().collect()
+    }
+
+    fn take(dices_used: [u32; 6], value: u32) -> TakeOption {
+        TakeOption {
+            dices_used: dices_used.into(),
+            value,
+        }
+    }
+
+    #[test]
+    fn ranked() {
+        let hand = hand_from_dices(Dices::from([1, 1, 3, 4, 4, 5]));
+
+        assert_eq!(
+            hand.takes(),
+            [
+                take([2, 0, 0, 0, 1, 0], 250),
+                take([2, 0, 0, 0, 0, 0], 200),
+                take([1, 0, 0, 0, 1, 0], 150),
+                take([1, 0, 0, 0, 0, 0], 100),
+                take([0, 0, 0, 0, 1, 0], 50),
+            ]
+        );
+    }
+
+    #[test]
+    fn one_take_per_dices() {
+        // three single 5s (150) are no take beside three 5s (500)
+        let hand = hand_from_dices(Dices::from([2, 3, 3, 5, 5, 5]));
+
+        assert_eq!(
+            hand.takes(),
+            [
+                take([0, 0, 0, 0, 3, 0], 500),
+                take([0, 0, 0, 0, 2, 0], 100),
+                take([0, 0, 0, 0, 1, 0], 50),
+            ]
+        );
+    }
+
+    #[test]
+    fn ranks_the_best_split_only() {
+        let takes = HashSet::from([
+            take([0, 0, 0, 0, 3, 0], 150),
+            take([0, 0, 0, 0, 3, 0], 500),
+            take([0, 0, 0, 0, 1, 0], 50),
+        ]);
+
+        assert_eq!(
+            *ranked_takes(takes),
+            [take([0, 0, 0, 0, 3, 0], 500), take([0, 0, 0, 0, 1, 0], 50)]
+        );
+    }
+
+    #[test]
+    fn from_parsed_dices() {
+        let hand = Hand::from_dices("115666".parse().unwrap()).unwrap();
+
+        assert_eq!(hand.takes()[0], take([2, 0, 0, 0, 1, 3], 850));
+    }
+
+    #[test]
+    fn invalid_dices() {
+        assert_eq!(
+            Hand::from_dices(Dices::from([1, 7])).err(),
+            Some(DicesError::OutOfRange(7))
+        );
+        assert_eq!(
+            Hand::from_dices(Dices::from([1; 11])).err(),
+            Some(DicesError::TooMany(11))
+        );
+
+        let four_sided = Ruleset {
+            sides: 4,
+            ..Ruleset::default()
         };
-        hand.analyze_dices();
-        hand
+        let hand = Hand::from_dices_with_rules(Dices::from([1, 5]), &four_sided);
+        assert!(matches!(
+            hand,
+            Err(KostkaError::InvalidDice(DicesError::OutOfRange(5)))
+        ));
+    }
+
+    #[test]
+    fn same_order_every_time() {
+        // more than six dices are enumerated into a fresh set every time
+        let dices = [1, 1, 1, 5, 5, 2, 3, 5];
+        let first = hand_from_dices(Dices::from(dices));
+        let second = hand_from_dices(Dices::from(dices));
+
+        assert_eq!(first.takes(), second.takes());
+        assert!(first.takes().windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn best_of_each_size() {
+        let hand = hand_from_dices(Dices::from([1, 1, 3, 4, 4, 5]));
+
+        assert_eq!(
+            hand.best_takes(),
+            [
+                take([2, 0, 0, 0, 1, 0], 250),
+                take([2, 0, 0, 0, 0, 0], 200),
+                take([1, 0, 0, 0, 0, 0], 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn best_takes_keep_hot_dice() {
+        let hand = hand_from_dices(Dices::from([1, 2, 3, 4, 5, 6]));
+
+        let best = hand.best_takes();
+        assert_eq!(best[0], take([1, 1, 1, 1, 1, 1], 2000));
+        assert_eq!(best.len(), 3);
     }
 
     #[test]
@@ -169,26 +392,26 @@ mod tests {
 
         let expected = [
             TakeOption {
-                dices_used: [1, 1, 1, 1, 1, 1],
+                dices_used: [1, 1, 1, 1, 1, 1].into(),
                 value: 2000,
             },
             TakeOption {
-                dices_used: [1, 0, 0, 0, 0, 0],
+                dices_used: [1, 0, 0, 0, 0, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [0, 0, 0, 0, 1, 0],
+                dices_used: [0, 0, 0, 0, 1, 0].into(),
                 value: 50,
             },
             TakeOption {
-                dices_used: [1, 0, 0, 0, 1, 0],
+                dices_used: [1, 0, 0, 0, 1, 0].into(),
                 value: 150,
             },
         ];
 
         let expected_hash = expected.iter().cloned().collect();
 
-        assert_eq!(hand.take_options, expected_hash);
+        assert_eq!(takes_set(&hand), expected_hash);
     }
 
     mod take_all {
@@ -252,18 +475,18 @@ mod tests {
 
             let expected = [
                 TakeOption {
-                    dices_used: [1, 0, 0, 0, 0, 0],
+                    dices_used: [1, 0, 0, 0, 0, 0].into(),
                     value: 100,
                 },
                 TakeOption {
-                    dices_used: [2, 0, 0, 0, 0, 0],
+                    dices_used: [2, 0, 0, 0, 0, 0].into(),
                     value: 200,
                 },
             ];
 
             let expected_hash = expected.iter().cloned().collect();
 
-            assert_eq!(hand.take_options, expected_hash);
+            assert_eq!(takes_set(&hand), expected_hash);
         }
 
         #[test]
@@ -273,30 +496,30 @@ mod tests {
 
             let expected = [
                 TakeOption {
-                    dices_used: [1, 0, 0, 0, 0, 0],
+                    dices_used: [1, 0, 0, 0, 0, 0].into(),
                     value: 100,
                 },
                 TakeOption {
-                    dices_used: [2, 0, 0, 0, 0, 0],
+                    dices_used: [2, 0, 0, 0, 0, 0].into(),
                     value: 200,
                 },
                 TakeOption {
-                    dices_used: [0, 0, 0, 0, 1, 0],
+                    dices_used: [0, 0, 0, 0, 1, 0].into(),
                     value: 50,
                 },
                 TakeOption {
-                    dices_used: [1, 0, 0, 0, 1, 0],
+                    dices_used: [1, 0, 0, 0, 1, 0].into(),
                     value: 150,
                 },
                 TakeOption {
-                    dices_used: [2, 0, 0, 0, 1, 0],
+                    dices_used: [2, 0, 0, 0, 1, 0].into(),
                     value: 250,
                 },
             ];
 
             let expected_hash = expected.iter().cloned().collect();
 
-            assert_eq!(hand.take_options, expected_hash);
+            assert_eq!(takes_set(&hand), expected_hash);
         }
 
         #[test]
@@ -304,24 +527,302 @@ mod tests {
             let dices = Dices::from([2, 2, 2, 6, 6, 6]);
             let hand = hand_from_dices(dices);
 
-            let expected = vec![
+            let expected = [
                 TakeOption {
-                    dices_used: [0, 3, 0, 0, 0, 0],
+                    dices_used: [0, 3, 0, 0, 0, 0].into(),
                     value: 200,
                 },
                 TakeOption {
-                    dices_used: [0, 0, 0, 0, 0, 3],
+                    dices_used: [0, 0, 0, 0, 0, 3].into(),
                     value: 600,
                 },
                 TakeOption {
-                    dices_used: [0, 3, 0, 0, 0, 3],
+                    dices_used: [0, 3, 0, 0, 0, 3].into(),
                     value: 800,
                 },
             ];
 
             let expected_hash = expected.iter().cloned().collect();
 
-            assert_eq!(hand.take_options, expected_hash);
+            assert_eq!(takes_set(&hand), expected_hash);
+        }
+    }
+
+    /// Invariants of the takes of arbitrary rolls
+    #[cfg(not(target_arch = "wasm32"))]
+    mod properties {
+        use super::*;
+        use crate::dice_m::MAX_SIDES;
+        use proptest::prelude::*;
+
+        /// Roll of up to six dices and the rules it is scored by, of any
+        /// sides
+        fn game() -> impl Strategy<Value = (Vec<i32>, Ruleset)> {
+            let points = (1..=20u32, 1..=20u32, 1..=50u32, 1..=50u32, 1..=20u32);
+            (1..=MAX_SIDES, points).prop_flat_map(
+                |(sides, (one, five, straight, triple_ones, triple_face))| {
+                    let rules = Ruleset {
+                        sides,
+                        one: one * 10,
+                        five: five * 10,
+                        straight: straight * 100,
+                        triple_ones: triple_ones * 100,
+                        triple_face: triple_face * 10,
+                        ..Ruleset::default()
+                    };
+                    let roll = prop::collection::vec(1..=sides as i32, 1..=6);
+                    (roll, Just(rules))
+                },
+            )
+        }
+
+        /// Hand enumerating its takes, a table per generated ruleset would
+        /// be slow to build
+        fn hand(roll: &[i32], rules: &Ruleset) -> Hand {
+            let mut dices = [Dice::from_value(0); MAX_DICES];
+            for (dice, &value) in dices.iter_mut().zip(roll) {
+                *dice = Dice::from_value(value);
+            }
+            let mut hand = Hand {
+                dices,
+                dices_len: roll.len(),
+                dice_counts: FaceCounts::new(rules.sides),
+                take_options: Arc::new([]),
+                rules: *rules,
+            };
+            hand.generate_counts();
+            hand.take_options = ranked_takes(enumerate_takes(&hand.dice_counts, rules));
+            hand
+        }
+
+        fn counts(roll: &[i32], sides: u32) -> FaceCounts {
+            let mut counts = FaceCounts::new(sides);
+            for &face in roll {
+                counts[(face as usize) - 1] += 1;
+            }
+            counts
+        }
+
+        /// Value of the most valuable partition of the dices into
+        /// combinations, trying every combination that fits
+        fn best_partition(counts: FaceCounts, rules: &Ruleset) -> Option<u32> {
+            if counts.total() == 0 {
+                return Some(0);
+            }
+            let sides = counts.sides();
+            let combinations = (1..=sides)
+                .flat_map(|face| (1..=6).filter_map(move |n| ScoredCombination::of_face(face, n)))
+                .chain([ScoredCombination::Straight])
+                .filter(|comb| comb.fits(sides));
+            combinations
+                .filter_map(|comb| {
+                    let rest = counts.checked_sub(&comb.dices_used(sides))?;
+                    Some(comb.score_with(rules) + best_partition(rest, rules)?)
+                })
+                .max()
+        }
+
+        proptest! {
+            #[test]
+            fn takes_fit_the_dices((roll, rules) in game()) {
+                let hand = hand(&roll, &rules);
+
+                for take in hand.get_takes() {
+                    prop_assert!(take.dices_count() > 0);
+                    prop_assert!(
+                        hand.dice_counts.checked_sub(&take.dices_used).is_some(),
+                        "{:?} in {:?}",
+                        take,
+                        roll
+                    );
+                }
+            }
+
+            #[test]
+            fn values_are_best_breakdowns((roll, rules) in game()) {
+                let hand = hand(&roll, &rules);
+
+                for take in hand.get_takes() {
+                    prop_assert_eq!(Some(take.value), best_partition(take.dices_used, &rules));
+
+                    let breakdown = hand.breakdown(take).unwrap();
+                    prop_assert_eq!(breakdown.value, take.value);
+
+                    let mut dices_used = FaceCounts::new(rules.sides);
+                    for comb in breakdown.combinations.iter() {
+                        for (n, used) in dices_used.iter_mut().zip(comb.dices_used(rules.sides)) {
+                            *n += used;
+                        }
+                    }
+                    prop_assert_eq!(dices_used, take.dices_used);
+                }
+            }
+
+            #[test]
+            fn use_all_means_every_dice((roll, rules) in game()) {
+                let hand = hand(&roll, &rules);
+                let counts = counts(&roll, rules.sides);
+
+                let use_all: Vec<TakeOption> =
+                    hand.takes_use_all().into_iter().copied().collect();
+                let expected: Vec<TakeOption> = best_partition(counts, &rules)
+                    .map(|value| TakeOption {
+                        dices_used: counts,
+                        value,
+                    })
+                    .into_iter()
+                    .collect();
+                prop_assert_eq!(use_all, expected);
+            }
+
+            #[test]
+            fn closed_under_combining((roll, rules) in game()) {
+                let hand = hand(&roll, &rules);
+                let by_dices: HashMap<FaceCounts, u32> = hand
+                    .get_takes()
+                    .map(|take| (take.dices_used, take.value))
+                    .collect();
+
+                for a in hand.get_takes() {
+                    for b in hand.get_takes() {
+                        let combined = a.combine(b);
+                        if hand.dice_counts.checked_sub(&combined.dices_used).is_some() {
+                            let value = by_dices.get(&combined.dices_used);
+                            prop_assert!(
+                                value.is_some_and(|&value| value >= combined.value),
+                                "{:?} + {:?} in {:?}",
+                                a,
+                                b,
+                                roll
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Previous take generation, growing the basic combinations by pairwise
+    /// sums of disjoint takes until nothing new appears
+    mod pairwise_reference {
+        use super::*;
+        use crate::dice_m::ScoredCombination;
+
+        struct PairwiseHand {
+            dice_counts: FaceCounts,
+            take_options: HashSet<TakeOption>,
+        }
+
+        impl PairwiseHand {
+            fn dices_with(&self, value: u32) -> u32 {
+                let dice_range = 1..=6;
+                assert!(dice_range.contains(&value));
+                self.dice_counts[(value as usize) - 1]
+            }
+
+            fn generate_basic_options(&self, rules: &Ruleset) -> HashSet<TakeOption> {
+                let mut options = HashSet::default();
+
+                for face in 1..=6u32 {
+                    let n_of_dices = self.dices_with(face);
+                    options.extend(TakeOption::one_face_all_combinations_with(
+                        face, n_of_dices, rules,
+                    ));
+                }
+
+                if self.dice_counts == [1, 1, 1, 1, 1, 1] {
+                    options.insert(TakeOption::from_combination_with(
+                        [1, 1, 1, 1, 1, 1].into(),
+                        ScoredCombination::Straight,
+                        rules,
+                    ));
+                }
+
+                options
+            }
+
+            fn takes_overlap(&self, first: &TakeOption, second: &TakeOption) -> bool {
+                use itertools::izip;
+
+                let available_dices = self.dice_counts.iter();
+                let first_dices = first.dices_used.iter();
+                let second_dices = second.dices_used.iter();
+
+                izip!(available_dices, first_dices, second_dices).any(|(&av, &f, &s)| av < f + s)
+            }
+
+            fn includes_take(&self, take: &TakeOption) -> bool {
+                self.take_options.iter().any(|x| x == take)
+            }
+
+            fn make_combinations(&self) -> HashSet<TakeOption> {
+                let mut new_combinations = HashSet::new();
+
+                for option in self.take_options.iter() {
+                    for option_ot in self.take_options.iter() {
+                        if !self.takes_overlap(option, option_ot) {
+                            let new_option = option.combine(option_ot);
+                            if !self.includes_take(&new_option) {
+                                new_combinations.insert(new_option);
+                            }
+                        }
+                    }
+                }
+
+                new_combinations
+            }
+
+            fn combine_options(&mut self) {
+                loop {
+                    let new_options = self.make_combinations();
+                    match new_options.len() {
+                        0 => break,
+                        _ => {
+                            self.take_options.extend(new_options);
+                        }
+                    }
+                }
+            }
+
+            fn pairwise_takes(dices: Dices, rules: &Ruleset) -> HashSet<TakeOption> {
+                let mut hand = PairwiseHand {
+                    dice_counts: Default::default(),
+                    take_options: Default::default(),
+                };
+                for dice in dices.iter() {
+                    hand.dice_counts[(dice.value as usize) - 1] += 1;
+                }
+                hand.take_options = hand.generate_basic_options(rules);
+                hand.combine_options();
+                hand.take_options
+            }
+        }
+
+        #[test]
+        fn same_takes_for_every_roll() {
+            let rules = Ruleset::default();
+            // the reference is slow, compute it once per multiset of faces
+            let mut expected: HashMap<FaceCounts, HashSet<TakeOption>> = HashMap::new();
+
+            for n in 1..=6u32 {
+                for index in 0..6usize.pow(n) {
+                    let roll: Vec<i32> = (0..n)
+                        .map(|i| (index / 6usize.pow(i) % 6) as i32 + 1)
+                        .collect();
+                    let hand =
+                        Hand::from_dices_with_rules(Dices::from(roll.as_slice()), &rules).unwrap();
+
+                    let expected = expected.entry(hand.dice_counts).or_insert_with(|| {
+                        PairwiseHand::pairwise_takes(Dices::from(roll.as_slice()), &rules)
+                    });
+                    assert_eq!(
+                        enumerate_takes(&hand.dice_counts, &rules),
+                        *expected,
+                        "roll {:?}",
+                        roll
+                    );
+                }
+            }
         }
     }
 }
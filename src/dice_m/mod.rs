@@ -1,11 +1,26 @@
 mod score;
 pub use score::ScoredCombination;
 
+mod ruleset;
+pub use ruleset::{Ruleset, StopPoints, MAX_DICES, MAX_POINTS, MAX_SIDES};
+
 mod dice;
-pub use dice::{Dice, Dices};
+pub use dice::{Dice, Dices, DicesError};
+
+mod face_counts;
+pub use face_counts::FaceCounts;
 
 mod take_option;
-pub use take_option::TakeOption;
+pub use take_option::{Breakdown, TakeOption};
 
 mod hand;
 pub use hand::Hand;
+
+mod rules_cache;
+pub(crate) use rules_cache::{RulesCache, CACHED_RULESETS};
+
+mod take_table;
+pub use take_table::TakeTable;
+
+mod analysis;
+pub use analysis::{analyze, analyze_all, distinct_rolls, DiceStats};
@@ -0,0 +1,146 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use super::Ruleset;
+use crate::KostkaError;
+
+/// Rulesets whose tables are kept, older ones are built again when needed
+pub(crate) const CACHED_RULESETS: usize = 8;
+
+/// Values built per ruleset, keeping only the most recently used ones.
+///
+/// Callers holding on to a value keep it alive after it is evicted.
+#[derive(Debug)]
+pub(crate) struct RulesCache<T> {
+    /// Most recently used first
+    entries: Mutex<VecDeque<(Ruleset, Arc<T>)>>,
+    capacity: usize,
+}
+
+impl<T> RulesCache<T> {
+    pub(crate) const fn new(capacity: usize) -> Self {
+        RulesCache {
+            entries: Mutex::new(VecDeque::new()),
+            capacity,
+        }
+    }
+
+    /// Value of `rules`, built by `build` unless cached.
+    ///
+    /// Builds without holding the lock, so other lookups go on meanwhile;
+    /// threads building the same value at once keep the first one cached.
+    pub(crate) fn get_or_build(
+        &self,
+        rules: &Ruleset,
+        build: impl FnOnce(&Ruleset) -> Result<T, KostkaError>,
+    ) -> Result<Arc<T>, KostkaError> {
+        if let Some(value) = self.use_cached(rules, None) {
+            return Ok(value);
+        }
+        let built = Arc::new(build(rules)?);
+        Ok(self
+            .use_cached(rules, Some(built))
+            .expect("Built value is cached"))
+    }
+
+    /// Cached value of `rules`, or `built` cached when there is none, moved
+    /// to the most recently used
+    fn use_cached(&self, rules: &Ruleset, built: Option<Arc<T>>) -> Option<Arc<T>> {
+        let mut entries = self.entries();
+        let value = match entries.iter().position(|(cached, _)| cached == rules) {
+            Some(i) => entries.remove(i).expect("Position is in range").1,
+            None => built?,
+        };
+        entries.push_front((*rules, value.clone()));
+        entries.truncate(self.capacity);
+        Some(value)
+    }
+
+    /// The entries stay consistent when a thread panics holding the lock,
+    /// nothing runs between changing them
+    fn entries(&self) -> MutexGuard<'_, VecDeque<(Ruleset, Arc<T>)>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(one: u32) -> Ruleset {
+        Ruleset {
+            one,
+            ..Ruleset::default()
+        }
+    }
+
+    #[test]
+    fn shares_cached_values() {
+        let cache = RulesCache::new(2);
+        let first = cache
+            .get_or_build(&rules(100), |rules| Ok(rules.one))
+            .unwrap();
+        let again = cache.get_or_build(&rules(100), |_| unreachable!()).unwrap();
+
+        assert!(Arc::ptr_eq(&first, &again));
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = RulesCache::new(2);
+        // whether the value had to be built
+        let built = |one| {
+            let mut built = false;
+            cache
+                .get_or_build(&rules(one), |rules| {
+                    built = true;
+                    Ok(rules.one)
+                })
+                .unwrap();
+            built
+        };
+
+        assert!(built(100));
+        assert!(built(200));
+        assert!(!built(100));
+        assert!(built(300)); // evicts 200
+        assert!(!built(100));
+        assert!(built(200));
+    }
+
+    #[test]
+    fn looks_up_while_building() {
+        let cache = RulesCache::new(2);
+        let value = cache
+            .get_or_build(&rules(100), |_| {
+                // would deadlock if the build held the lock
+                Ok(*cache.get_or_build(&rules(200), |rules| Ok(rules.one))?)
+            })
+            .unwrap();
+
+        assert_eq!(*value, 200);
+    }
+
+    #[test]
+    fn panicking_builds_leave_the_cache_usable() {
+        let cache: RulesCache<u32> = RulesCache::new(2);
+        let panicked = std::panic::catch_unwind(|| {
+            cache.get_or_build(&rules(100), |_| panic!("Build failed"))
+        });
+
+        assert!(panicked.is_err());
+        assert_eq!(*cache.get_or_build(&rules(100), |_| Ok(1)).unwrap(), 1);
+    }
+
+    #[test]
+    fn failed_builds_are_not_cached() {
+        let cache: RulesCache<u32> = RulesCache::new(2);
+
+        assert!(cache
+            .get_or_build(&rules(100), |_| Err(KostkaError::NotAsked))
+            .is_err());
+        assert_eq!(*cache.get_or_build(&rules(100), |_| Ok(1)).unwrap(), 1);
+    }
+}
@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+
+use super::ScoredCombination;
+use crate::KostkaError;
+
+/// Most dices a game may be played with
+pub const MAX_DICES: usize = 10;
+
+/// Most sides of a dice, a d12
+pub const MAX_SIDES: u32 = 12;
+
+/// Highest value of a single combination or bonus, keeps turn scores in `u32`
+pub const MAX_POINTS: u32 = 100_000;
+
+/// Numbers of dices left to roll after a take with which a player may bank
+/// the turn score, 0 standing for hot dice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Vec<usize>", into = "Vec<usize>")]
+pub struct StopPoints(u16);
+
+impl StopPoints {
+    /// After any take, except that hot dice are always rolled again
+    pub const ANY_TAKE: StopPoints = StopPoints(((1 << (MAX_DICES + 1)) - 1) & !1);
+
+    /// Errs on counts above [`MAX_DICES`]
+    pub fn new(dices_left: &[usize]) -> Result<Self, KostkaError> {
+        dices_left.iter().try_fold(StopPoints(0), |points, &n| {
+            if n > MAX_DICES {
+                return Err(KostkaError::Ruleset(format!(
+                    "stop with {} dices left, expected 0 to {}",
+                    n, MAX_DICES
+                )));
+            }
+            Ok(StopPoints(points.0 | 1 << n))
+        })
+    }
+
+    /// Stop points of the bits set in `bits`, bit `n` for `n` dices left
+    pub fn from_bits(bits: u16) -> Self {
+        StopPoints(bits & ((1 << (MAX_DICES + 1)) - 1))
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn contains(&self, dices_left: usize) -> bool {
+        dices_left <= MAX_DICES && self.0 & 1 << dices_left != 0
+    }
+
+    /// Counts of dices left in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..=MAX_DICES).filter(|&n| self.contains(n))
+    }
+}
+
+impl Default for StopPoints {
+    fn default() -> Self {
+        Self::ANY_TAKE
+    }
+}
+
+impl TryFrom<Vec<usize>> for StopPoints {
+    type Error = KostkaError;
+
+    fn try_from(dices_left: Vec<usize>) -> Result<Self, Self::Error> {
+        Self::new(&dices_left)
+    }
+}
+
+impl From<StopPoints> for Vec<usize> {
+    fn from(points: StopPoints) -> Self {
+        points.iter().collect()
+    }
+}
+
+/// Dices of the game and point values of the scoring combinations.
+///
+/// Groups of four or more dice double the value of the smaller group,
+/// e.g. a quad is worth twice a triple of the same face. A straight is one
+/// dice of every face from 1 to 6, dices of fewer sides roll none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ruleset {
+    /// Dices rolled at the start of a turn and after hot dice, 1 to [`MAX_DICES`]
+    pub dices: usize,
+    /// Sides of every dice, 1 to [`MAX_SIDES`]
+    pub sides: u32,
+    pub one: u32,
+    pub five: u32,
+    pub straight: u32,
+    pub triple_ones: u32,
+    /// Triple of face `v` (other than ones) is worth `v * triple_face`
+    pub triple_face: u32,
+    /// Points added to the turn score every time all the dices score
+    pub hot_dice_bonus: u32,
+    /// When the player may end the turn, the turn goes on otherwise
+    pub stop_points: StopPoints,
+    /// Smallest turn score the player may bank
+    pub min_bank: u32,
+    /// The next player may take over the turn score and dices left by a
+    /// player who banked
+    pub piggyback: bool,
+}
+
+impl Ruleset {
+    pub fn new() -> Self {
+        Ruleset {
+            dices: 6,
+            sides: 6,
+            one: 100,
+            five: 50,
+            straight: 2000,
+            triple_ones: 1000,
+            triple_face: 100,
+            hot_dice_bonus: 0,
+            stop_points: StopPoints::ANY_TAKE,
+            min_bank: 0,
+            piggyback: false,
+        }
+    }
+
+    /// Errs when the dices, sides or point values are outside of the
+    /// supported limits, every combination must be worth at least a point
+    pub fn validate(&self) -> Result<(), KostkaError> {
+        if !(1..=MAX_DICES).contains(&self.dices) {
+            return Err(KostkaError::Ruleset(format!(
+                "{} dices, expected 1 to {}",
+                self.dices, MAX_DICES
+            )));
+        }
+        if !(1..=MAX_SIDES).contains(&self.sides) {
+            return Err(KostkaError::Ruleset(format!(
+                "{} sides, expected 1 to {}",
+                self.sides, MAX_SIDES
+            )));
+        }
+        let points = [
+            ("one", self.one),
+            ("five", self.five),
+            ("straight", self.straight),
+            ("triple_ones", self.triple_ones),
+            ("triple_face", self.triple_face),
+        ];
+        for (name, value) in points {
+            if !(1..=MAX_POINTS).contains(&value) {
+                return Err(KostkaError::Ruleset(format!(
+                    "{} worth {}, expected 1 to {}",
+                    name, value, MAX_POINTS
+                )));
+            }
+        }
+        if self.hot_dice_bonus > MAX_POINTS {
+            return Err(KostkaError::Ruleset(format!(
+                "hot dice bonus {}, expected at most {}",
+                self.hot_dice_bonus, MAX_POINTS
+            )));
+        }
+        Ok(())
+    }
+
+    /// The dices and point values the takes depend on, the rest of the turn
+    /// rules at their defaults
+    pub fn scoring(&self) -> Ruleset {
+        Ruleset {
+            dices: self.dices,
+            sides: self.sides,
+            one: self.one,
+            five: self.five,
+            straight: self.straight,
+            triple_ones: self.triple_ones,
+            triple_face: self.triple_face,
+            ..Ruleset::default()
+        }
+    }
+
+    pub fn score(&self, comb: &ScoredCombination) -> u32 {
+        match comb {
+            ScoredCombination::Straight => self.straight,
+            ScoredCombination::One => self.one,
+            ScoredCombination::Five => self.five,
+            ScoredCombination::TwoOnes => self.one * 2,
+            ScoredCombination::TwoFives => self.five * 2,
+            ScoredCombination::Triple(v) => self.triple(*v),
+            ScoredCombination::Quad(v) => self.triple(*v) * 2,
+            ScoredCombination::Quint(v) => self.triple(*v) * 4,
+            ScoredCombination::Sext(v) => self.triple(*v) * 8,
+        }
+    }
+
+    fn triple(&self, face: u32) -> u32 {
+        match face {
+            1 => self.triple_ones,
+            v => v * self.triple_face,
+        }
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_points() {
+        let few = StopPoints::new(&[1, 2]).unwrap();
+        assert!(few.contains(2) && !few.contains(3) && !few.contains(0));
+        assert_eq!(few.iter().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(StopPoints::from_bits(few.bits()), few);
+
+        assert!(!StopPoints::ANY_TAKE.contains(0));
+        assert!((1..=MAX_DICES).all(|n| StopPoints::ANY_TAKE.contains(n)));
+        assert!(StopPoints::new(&[MAX_DICES + 1]).is_err());
+    }
+
+    #[test]
+    fn sides_up_to_a_d12() {
+        for (sides, valid) in [
+            (0, false),
+            (1, true),
+            (8, true),
+            (MAX_SIDES, true),
+            (13, false),
+        ] {
+            let rules = Ruleset {
+                sides,
+                ..Ruleset::default()
+            };
+            assert_eq!(rules.validate().is_ok(), valid, "{} sides", sides);
+        }
+    }
+
+    #[test]
+    fn rejects_worthless_and_huge_points() {
+        assert!(Ruleset::default().validate().is_ok());
+        for rules in [
+            Ruleset {
+                five: 0,
+                ..Ruleset::default()
+            },
+            Ruleset {
+                straight: 0,
+                ..Ruleset::default()
+            },
+            Ruleset {
+                triple_face: MAX_POINTS + 1,
+                ..Ruleset::default()
+            },
+            Ruleset {
+                hot_dice_bonus: u32::MAX,
+                ..Ruleset::default()
+            },
+        ] {
+            assert!(matches!(rules.validate(), Err(KostkaError::Ruleset(_))));
+        }
+    }
+
+    #[test]
+    fn stop_points_as_a_list() {
+        let rules: Ruleset = serde_json::from_str(r#"{"stop_points": [0, 2]}"#).unwrap();
+        assert_eq!(rules.stop_points, StopPoints::new(&[0, 2]).unwrap());
+        assert_eq!(rules.dices, 6);
+
+        let json = serde_json::to_value(Ruleset::default()).unwrap();
+        assert_eq!(
+            json["stop_points"],
+            serde_json::json!([1, 2, 3, 4, 5, 6, 7, 8, 9, 10])
+        );
+        assert!(serde_json::from_str::<Ruleset>(r#"{"stop_points": [11]}"#).is_err());
+    }
+}
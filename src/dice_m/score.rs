@@ -1,4 +1,8 @@
-#[derive(Debug)]
+use std::fmt::Display;
+
+use super::{FaceCounts, Ruleset};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScoredCombination {
     Straight,
     One,
@@ -12,23 +16,75 @@ pub enum ScoredCombination {
 }
 
 impl ScoredCombination {
+    /// Combination of exactly `n` dices showing `face`
+    pub fn of_face(face: u32, n: u32) -> Option<Self> {
+        match (n, face) {
+            (1, 1) => Some(ScoredCombination::One),
+            (1, 5) => Some(ScoredCombination::Five),
+            (2, 1) => Some(ScoredCombination::TwoOnes),
+            (2, 5) => Some(ScoredCombination::TwoFives),
+            (3, v) => Some(ScoredCombination::Triple(v)),
+            (4, v) => Some(ScoredCombination::Quad(v)),
+            (5, v) => Some(ScoredCombination::Quint(v)),
+            (6, v) => Some(ScoredCombination::Sext(v)),
+            _ => None,
+        }
+    }
+
+    /// Whether dices of `sides` sides can show the combination
+    pub fn fits(&self, sides: u32) -> bool {
+        match *self {
+            ScoredCombination::Straight => sides >= 6,
+            ScoredCombination::One | ScoredCombination::TwoOnes => sides >= 1,
+            ScoredCombination::Five | ScoredCombination::TwoFives => sides >= 5,
+            ScoredCombination::Triple(v)
+            | ScoredCombination::Quad(v)
+            | ScoredCombination::Quint(v)
+            | ScoredCombination::Sext(v) => (1..=sides).contains(&v),
+        }
+    }
+
+    /// Dices of every face of `sides` sided dices the combination is made
+    /// of, panics when it does not fit them
+    pub fn dices_used(&self, sides: u32) -> FaceCounts {
+        assert!(self.fits(sides), "{} on {} sided dices", self, sides);
+        let mut dices_used = FaceCounts::new(sides);
+        match *self {
+            ScoredCombination::Straight => dices_used[..6].fill(1),
+            ScoredCombination::One => dices_used[0] = 1,
+            ScoredCombination::Five => dices_used[4] = 1,
+            ScoredCombination::TwoOnes => dices_used[0] = 2,
+            ScoredCombination::TwoFives => dices_used[4] = 2,
+            ScoredCombination::Triple(v) => dices_used[v as usize - 1] = 3,
+            ScoredCombination::Quad(v) => dices_used[v as usize - 1] = 4,
+            ScoredCombination::Quint(v) => dices_used[v as usize - 1] = 5,
+            ScoredCombination::Sext(v) => dices_used[v as usize - 1] = 6,
+        }
+        dices_used
+    }
+
+    /// Score under the default rules
     pub fn to_score(&self) -> u32 {
+        self.score_with(&Ruleset::default())
+    }
+
+    pub fn score_with(&self, rules: &Ruleset) -> u32 {
+        rules.score(self) //todo dohozená straight
+    }
+}
+
+impl Display for ScoredCombination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ScoredCombination::Straight => 2000, //todo dohozená
-            ScoredCombination::One => 100,
-            ScoredCombination::Five => 50,
-            ScoredCombination::TwoOnes => 200,
-            ScoredCombination::TwoFives => 100,
-            // ones
-            ScoredCombination::Triple(1) => 1000,
-            ScoredCombination::Quad(1) => 1000 * 2,
-            ScoredCombination::Quint(1) => 1000 * 4,
-            ScoredCombination::Sext(1) => 1000 * 8,
-            // groups
-            ScoredCombination::Triple(v) => v * 100,
-            ScoredCombination::Quad(v) => v * 100 * 2,
-            ScoredCombination::Quint(v) => v * 100 * 4,
-            ScoredCombination::Sext(v) => v * 100 * 8,
+            ScoredCombination::Straight => write!(f, "straight"),
+            ScoredCombination::One => write!(f, "one 1"),
+            ScoredCombination::Five => write!(f, "one 5"),
+            ScoredCombination::TwoOnes => write!(f, "two 1s"),
+            ScoredCombination::TwoFives => write!(f, "two 5s"),
+            ScoredCombination::Triple(v) => write!(f, "three {}s", v),
+            ScoredCombination::Quad(v) => write!(f, "four {}s", v),
+            ScoredCombination::Quint(v) => write!(f, "five {}s", v),
+            ScoredCombination::Sext(v) => write!(f, "six {}s", v),
         }
     }
 }
@@ -1,11 +1,16 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt::Display};
 
-use super::ScoredCombination;
+use serde::{Deserialize, Serialize};
 
-//todo own partialeq
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use super::{FaceCounts, Ruleset, ScoredCombination};
+
+/// Dices set aside from a roll and their value.
+///
+/// Takes are ordered by value, then by fewer dices used and finally by
+/// `dices_used`, so the greatest take scores the most with the fewest dices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TakeOption {
-    pub dices_used: [u32; 6],
+    pub dices_used: FaceCounts,
     pub value: u32,
 }
 
@@ -18,89 +23,66 @@ impl TakeOption {
     }
 
     pub fn dices_count(&self) -> usize {
-        self.dices_used.iter().sum::<u32>() as usize
+        self.dices_used.total() as usize
     }
 
     pub fn value(&self) -> u32 {
         self.value
     }
 
-    pub fn from_combination(dices_used: [u32; 6], comb: ScoredCombination) -> Self {
-        let value = comb.to_score();
+    pub fn from_combination(dices_used: FaceCounts, comb: ScoredCombination) -> Self {
+        Self::from_combination_with(dices_used, comb, &Ruleset::default())
+    }
+
+    pub fn from_combination_with(
+        dices_used: FaceCounts,
+        comb: ScoredCombination,
+        rules: &Ruleset,
+    ) -> Self {
+        let value = comb.score_with(rules);
         TakeOption { dices_used, value }
     }
 
     pub fn one_face_all_combinations(face: u32, n: u32) -> HashSet<TakeOption> {
-        let mut options = HashSet::new();
+        Self::one_face_all_combinations_with(face, n, &Ruleset::default())
+    }
 
+    pub fn one_face_all_combinations_with(
+        face: u32,
+        n: u32,
+        rules: &Ruleset,
+    ) -> HashSet<TakeOption> {
+        let mut options = HashSet::new();
         for i in 1..=n {
-            match i {
-                1 => match face {
-                    1 => options.insert(TakeOption::from_combination(
-                        [1, 0, 0, 0, 0, 0],
-                        ScoredCombination::One,
-                    )),
-                    5 => options.insert(TakeOption::from_combination(
-                        [0, 0, 0, 0, 1, 0],
-                        ScoredCombination::Five,
-                    )),
-                    _ => false,
-                },
-                2 => match face {
-                    1 => options.insert(TakeOption::from_combination(
-                        [2, 0, 0, 0, 0, 0],
-                        ScoredCombination::TwoOnes,
-                    )),
-                    5 => options.insert(TakeOption::from_combination(
-                        [0, 0, 0, 0, 2, 0],
-                        ScoredCombination::TwoFives,
-                    )),
-                    _ => false,
-                },
-                3 => {
-                    let mut dices_used = [0; 6];
-                    dices_used[(face as usize) - 1] = 3;
-                    options.insert(TakeOption::from_combination(
-                        dices_used,
-                        ScoredCombination::Triple(face),
-                    ))
-                }
-                4 => {
-                    let mut dices_used = [0; 6];
-                    dices_used[(face as usize) - 1] = 4;
-                    options.insert(TakeOption::from_combination(
-                        dices_used,
-                        ScoredCombination::Quad(face),
-                    ))
-                }
-                5 => {
-                    let mut dices_used = [0; 6];
-                    dices_used[(face as usize) - 1] = 5;
-                    options.insert(TakeOption::from_combination(
-                        dices_used,
-                        ScoredCombination::Quint(face),
-                    ))
-                }
-                6 => {
-                    let mut dices_used = [0; 6];
-                    dices_used[(face as usize) - 1] = 6;
-                    options.insert(TakeOption::from_combination(
-                        dices_used,
-                        ScoredCombination::Sext(face),
-                    ))
-                }
-                0 => false,
-                _ => panic!("dices_with returned outside of <0;6>"),
-            };
+            if let Some(comb) = ScoredCombination::of_face(face, i) {
+                let dices_used = comb.dices_used(rules.sides);
+                options.insert(TakeOption::from_combination_with(dices_used, comb, rules));
+            }
         }
 
         options
     }
 
+    /// Take of all `dices_used` scored by their most valuable split into
+    /// combinations, None if some of the dices do not score
+    pub fn best_of(dices_used: FaceCounts, rules: &Ruleset) -> Option<TakeOption> {
+        let value = best_split(dices_used, rules)?.value;
+        Some(TakeOption { dices_used, value })
+    }
+
+    /// Most valuable split of the dices into combinations, None if some of
+    /// the dices do not score.
+    ///
+    /// The split is recomputed from `dices_used`, so its total differs from
+    /// `value` when the take was scored under other rules.
+    pub fn breakdown(&self, rules: &Ruleset) -> Option<Breakdown> {
+        best_split(self.dices_used, rules)
+    }
+
     pub fn combine(&self, other: &TakeOption) -> TakeOption {
-        let mut dices_used: [u32; 6] = Default::default();
-        for (i, (aval, bval)) in self.dices_used.iter().zip(&other.dices_used).enumerate() {
-            dices_used[i] = aval + bval;
+        let mut dices_used = self.dices_used;
+        for (n, used) in dices_used.iter_mut().zip(&other.dices_used) {
+            *n += used;
         }
         TakeOption {
             dices_used,
@@ -109,6 +91,118 @@ impl TakeOption {
     }
 }
 
+/// Most valuable split of the dices into combinations, None if some of
+/// the dices do not score
+fn best_split(dices_used: FaceCounts, rules: &Ruleset) -> Option<Breakdown> {
+    let by_faces = (1..=dices_used.sides())
+        .map(|face| best_face_split(face, dices_used.of_face(face), rules))
+        .collect::<Option<Vec<Vec<_>>>>()
+        .map(|splits| Breakdown::new(splits.concat(), rules));
+
+    let straight = ScoredCombination::Straight;
+    let with_straight = straight
+        .fits(dices_used.sides())
+        .then(|| dices_used.checked_sub(&straight.dices_used(dices_used.sides())))
+        .flatten()
+        .and_then(|rest| best_split(rest, rules))
+        .map(|rest| {
+            let mut combinations = vec![straight];
+            combinations.extend(rest.combinations);
+            Breakdown::new(combinations, rules)
+        });
+
+    match (by_faces, with_straight) {
+        (Some(a), Some(b)) if b.value > a.value => Some(b),
+        (None, b) => b,
+        (a, _) => a,
+    }
+}
+
+/// Values of every split of `n` dices showing `face` into combinations,
+/// ascending, none if they do not score
+pub(crate) fn one_face_values(face: u32, n: u32, rules: &Ruleset) -> Vec<u32> {
+    if n == 0 {
+        return vec![0];
+    }
+    let mut values: Vec<u32> = (1..=n)
+        .filter_map(|group| ScoredCombination::of_face(face, group).map(|comb| (group, comb)))
+        .flat_map(|(group, comb)| {
+            let value = comb.score_with(rules);
+            one_face_values(face, n - group, rules)
+                .into_iter()
+                .map(move |rest| value + rest)
+        })
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+/// Most valuable split of `n` dices showing `face` into combinations
+fn best_face_split(face: u32, n: u32, rules: &Ruleset) -> Option<Vec<ScoredCombination>> {
+    if n == 0 {
+        return Some(Vec::new());
+    }
+    (1..=n)
+        .filter_map(|group| {
+            let comb = ScoredCombination::of_face(face, group)?;
+            let mut split = best_face_split(face, n - group, rules)?;
+            split.insert(0, comb);
+            Some(split)
+        })
+        .max_by_key(|split| split.iter().map(|comb| comb.score_with(rules)).sum::<u32>())
+}
+
+/// Combinations a take is made of, with their values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown {
+    pub combinations: Vec<ScoredCombination>,
+    pub values: Vec<u32>,
+    /// Sum of the values
+    pub value: u32,
+}
+
+impl Breakdown {
+    fn new(combinations: Vec<ScoredCombination>, rules: &Ruleset) -> Self {
+        let values: Vec<u32> = combinations
+            .iter()
+            .map(|comb| comb.score_with(rules))
+            .collect();
+        Breakdown {
+            combinations,
+            value: values.iter().sum(),
+            values,
+        }
+    }
+}
+
+impl Display for Breakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (comb, value)) in self.combinations.iter().zip(&self.values).enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{} ({})", comb, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for TakeOption {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value
+            .cmp(&other.value)
+            .then(other.dices_count().cmp(&self.dices_count()))
+            .then(self.dices_used.cmp(&other.dices_used))
+    }
+}
+
+impl PartialOrd for TakeOption {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Default for TakeOption {
     fn default() -> Self {
         Self::new()
@@ -119,13 +213,114 @@ impl Default for TakeOption {
 mod tests {
 
     use super::TakeOption;
+    use crate::dice_m::{FaceCounts, Ruleset, ScoredCombination, TakeTable};
     use std::collections::HashSet;
 
+    #[test]
+    fn order() {
+        let take = |dices_used: [u32; 6], value| TakeOption {
+            dices_used: dices_used.into(),
+            value,
+        };
+
+        assert!(take([0, 0, 0, 0, 1, 0], 50) < take([1, 0, 0, 0, 0, 0], 100));
+        assert!(take([0, 0, 0, 0, 2, 0], 100) < take([1, 0, 0, 0, 0, 0], 100));
+        assert!(take([1, 0, 0, 0, 1, 0], 150) < take([1, 0, 0, 0, 2, 0], 200));
+        assert!(take([0, 0, 0, 0, 0, 3], 600) > take([0, 0, 0, 0, 3, 0], 500));
+    }
+
+    #[test]
+    fn breakdown_of_combined() {
+        let take = TakeOption {
+            dices_used: [1, 0, 0, 0, 3, 0].into(),
+            value: 600,
+        };
+        let breakdown = take.breakdown(&Ruleset::default()).unwrap();
+
+        assert_eq!(
+            breakdown.combinations,
+            [ScoredCombination::One, ScoredCombination::Triple(5)]
+        );
+        assert_eq!(breakdown.value, 600);
+        assert_eq!(breakdown.to_string(), "one 1 (100) + three 5s (500)");
+    }
+
+    #[test]
+    fn breakdown_prefers_straight() {
+        let take = TakeOption {
+            dices_used: [2, 1, 1, 1, 1, 1].into(),
+            value: 2100,
+        };
+        let breakdown = take.breakdown(&Ruleset::default()).unwrap();
+
+        assert_eq!(
+            breakdown.combinations,
+            [ScoredCombination::Straight, ScoredCombination::One]
+        );
+    }
+
+    #[test]
+    fn high_faces() {
+        let rules = Ruleset {
+            sides: 8,
+            ..Ruleset::default()
+        };
+        let take = TakeOption::best_of([1, 0, 0, 0, 0, 0, 0, 3].into(), &rules).unwrap();
+
+        assert_eq!(take.value, 100 + 800);
+        assert_eq!(
+            take.breakdown(&rules).unwrap().combinations,
+            [ScoredCombination::One, ScoredCombination::Triple(8)]
+        );
+    }
+
+    #[test]
+    fn no_breakdown_of_non_scoring() {
+        let take = TakeOption {
+            dices_used: [0, 1, 0, 0, 0, 0].into(),
+            value: 0,
+        };
+
+        assert_eq!(take.breakdown(&Ruleset::default()), None);
+    }
+
+    #[test]
+    fn breakdowns_match_values() {
+        let rules = Ruleset::default();
+        let table = TakeTable::new(&rules).unwrap();
+
+        let mut counts = FaceCounts::new(rules.sides);
+        loop {
+            if let Some(takes) = table.takes(&counts) {
+                for take in takes.iter() {
+                    let breakdown = take.breakdown(&rules).unwrap();
+                    let mut dices_used = FaceCounts::new(rules.sides);
+                    for comb in breakdown.combinations.iter() {
+                        for (n, used) in dices_used.iter_mut().zip(comb.dices_used(rules.sides)) {
+                            *n += used;
+                        }
+                    }
+                    assert_eq!(breakdown.value, take.value, "{:?}", take);
+                    assert_eq!(dices_used, take.dices_used);
+                }
+            }
+
+            // next counts of at most six dices of every face
+            match counts.iter().position(|&n| n < 6) {
+                Some(face) => {
+                    counts[face] += 1;
+                    counts[..face].fill(0);
+                }
+                None => break,
+            }
+        }
+    }
+
     #[test]
     fn one() {
         let options = TakeOption::one_face_all_combinations(1, 1);
         let expected = [TakeOption {
-            dices_used: [1, 0, 0, 0, 0, 0],
+            dices_used: [1, 0, 0, 0, 0, 0].into(),
             value: 100,
         }];
 
@@ -139,11 +334,11 @@ mod tests {
         let options = TakeOption::one_face_all_combinations(1, 2);
         let expected = [
             TakeOption {
-                dices_used: [1, 0, 0, 0, 0, 0],
+                dices_used: [1, 0, 0, 0, 0, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [2, 0, 0, 0, 0, 0],
+                dices_used: [2, 0, 0, 0, 0, 0].into(),
                 value: 200,
             },
         ];
@@ -158,15 +353,15 @@ mod tests {
         let options = TakeOption::one_face_all_combinations(1, 3);
         let expected = [
             TakeOption {
-                dices_used: [1, 0, 0, 0, 0, 0],
+                dices_used: [1, 0, 0, 0, 0, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [2, 0, 0, 0, 0, 0],
+                dices_used: [2, 0, 0, 0, 0, 0].into(),
                 value: 200,
             },
             TakeOption {
-                dices_used: [3, 0, 0, 0, 0, 0],
+                dices_used: [3, 0, 0, 0, 0, 0].into(),
                 value: 1000,
             },
         ];
@@ -181,19 +376,19 @@ mod tests {
         let options = TakeOption::one_face_all_combinations(1, 4);
         let expected = [
             TakeOption {
-                dices_used: [1, 0, 0, 0, 0, 0],
+                dices_used: [1, 0, 0, 0, 0, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [2, 0, 0, 0, 0, 0],
+                dices_used: [2, 0, 0, 0, 0, 0].into(),
                 value: 200,
             },
             TakeOption {
-                dices_used: [3, 0, 0, 0, 0, 0],
+                dices_used: [3, 0, 0, 0, 0, 0].into(),
                 value: 1000,
             },
             TakeOption {
-                dices_used: [4, 0, 0, 0, 0, 0],
+                dices_used: [4, 0, 0, 0, 0, 0].into(),
                 value: 2000,
             },
         ];
@@ -208,23 +403,23 @@ mod tests {
         let options = TakeOption::one_face_all_combinations(1, 5);
         let expected = [
             TakeOption {
-                dices_used: [1, 0, 0, 0, 0, 0],
+                dices_used: [1, 0, 0, 0, 0, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [2, 0, 0, 0, 0, 0],
+                dices_used: [2, 0, 0, 0, 0, 0].into(),
                 value: 200,
             },
             TakeOption {
-                dices_used: [3, 0, 0, 0, 0, 0],
+                dices_used: [3, 0, 0, 0, 0, 0].into(),
                 value: 1000,
             },
             TakeOption {
-                dices_used: [4, 0, 0, 0, 0, 0],
+                dices_used: [4, 0, 0, 0, 0, 0].into(),
                 value: 2000,
             },
             TakeOption {
-                dices_used: [5, 0, 0, 0, 0, 0],
+                dices_used: [5, 0, 0, 0, 0, 0].into(),
                 value: 4000,
             },
         ];
@@ -239,27 +434,27 @@ mod tests {
         let options = TakeOption::one_face_all_combinations(1, 6);
         let expected = [
             TakeOption {
-                dices_used: [1, 0, 0, 0, 0, 0],
+                dices_used: [1, 0, 0, 0, 0, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [2, 0, 0, 0, 0, 0],
+                dices_used: [2, 0, 0, 0, 0, 0].into(),
                 value: 200,
             },
             TakeOption {
-                dices_used: [3, 0, 0, 0, 0, 0],
+                dices_used: [3, 0, 0, 0, 0, 0].into(),
                 value: 1000,
             },
             TakeOption {
-                dices_used: [4, 0, 0, 0, 0, 0],
+                dices_used: [4, 0, 0, 0, 0, 0].into(),
                 value: 2000,
             },
             TakeOption {
-                dices_used: [5, 0, 0, 0, 0, 0],
+                dices_used: [5, 0, 0, 0, 0, 0].into(),
                 value: 4000,
             },
             TakeOption {
-                dices_used: [6, 0, 0, 0, 0, 0],
+                dices_used: [6, 0, 0, 0, 0, 0].into(),
                 value: 8000,
             },
         ];
@@ -291,43 +486,43 @@ mod tests {
 
         let expected = [
             TakeOption {
-                dices_used: [1, 0, 0, 0, 0, 0],
+                dices_used: [1, 0, 0, 0, 0, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [2, 0, 0, 0, 0, 0],
+                dices_used: [2, 0, 0, 0, 0, 0].into(),
                 value: 200,
             },
             TakeOption {
-                dices_used: [3, 0, 0, 0, 0, 0],
+                dices_used: [3, 0, 0, 0, 0, 0].into(),
                 value: 1000,
             },
             TakeOption {
-                dices_used: [0, 3, 0, 0, 0, 0],
+                dices_used: [0, 3, 0, 0, 0, 0].into(),
                 value: 200,
             },
             TakeOption {
-                dices_used: [0, 0, 3, 0, 0, 0],
+                dices_used: [0, 0, 3, 0, 0, 0].into(),
                 value: 300,
             },
             TakeOption {
-                dices_used: [0, 0, 0, 3, 0, 0],
+                dices_used: [0, 0, 0, 3, 0, 0].into(),
                 value: 400,
             },
             TakeOption {
-                dices_used: [0, 0, 0, 0, 1, 0],
+                dices_used: [0, 0, 0, 0, 1, 0].into(),
                 value: 50,
             },
             TakeOption {
-                dices_used: [0, 0, 0, 0, 2, 0],
+                dices_used: [0, 0, 0, 0, 2, 0].into(),
                 value: 100,
             },
             TakeOption {
-                dices_used: [0, 0, 0, 0, 3, 0],
+                dices_used: [0, 0, 0, 0, 3, 0].into(),
                 value: 500,
             },
             TakeOption {
-                dices_used: [0, 0, 0, 0, 0, 3],
+                dices_used: [0, 0, 0, 0, 0, 3].into(),
                 value: 600,
             },
         ];
@@ -342,11 +537,11 @@ mod tests {
         let options = TakeOption::one_face_all_combinations(4, 4);
         let expected = [
             TakeOption {
-                dices_used: [0, 0, 0, 3, 0, 0],
+                dices_used: [0, 0, 0, 3, 0, 0].into(),
                 value: 400,
             },
             TakeOption {
-                dices_used: [0, 0, 0, 4, 0, 0],
+                dices_used: [0, 0, 0, 4, 0, 0].into(),
                 value: 800,
             },
         ];
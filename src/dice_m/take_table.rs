@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+    sync::Arc,
+};
+
+use super::{
+    hand::{enumerate_takes, ranked_takes},
+    FaceCounts, RulesCache, Ruleset, TakeOption, CACHED_RULESETS,
+};
+use crate::KostkaError;
+
+/// Fewest dices a table holds takes for, the most compact hands hold
+const MIN_TABLE_DICES: u32 = 6;
+
+/// Most multisets of a table, hands of more dices generate their takes
+const MAX_TABLE_ENTRIES: u64 = 20_000;
+
+/// Takes of every multiset of up to the ruleset's dices, but at least six.
+///
+/// There are only 923 multisets of up to six six-sided dices, so hands look
+/// their takes up instead of generating them on every roll. Dices of many
+/// sides have so many multisets that the table stops at six dices.
+#[derive(Debug)]
+pub struct TakeTable {
+    rules: Ruleset,
+    /// Most dices of a multiset in the table
+    dices: u32,
+    /// Keyed by the packed counts of the faces
+    entries: HashMap<u64, Arc<[TakeOption]>, BuildHasherDefault<PackedHasher>>,
+}
+
+impl TakeTable {
+    /// Errs when `rules` are not supported
+    pub fn new(rules: &Ruleset) -> Result<Self, KostkaError> {
+        rules.validate()?;
+
+        let dices = (MIN_TABLE_DICES..=(rules.dices as u32).max(MIN_TABLE_DICES))
+            .rev()
+            .find(|&n| multisets(n, rules.sides) <= MAX_TABLE_ENTRIES)
+            .unwrap_or(MIN_TABLE_DICES);
+
+        let mut all_counts = Vec::new();
+        push_counts(&mut FaceCounts::new(rules.sides), 0, dices, &mut all_counts);
+        let entries = all_counts
+            .into_iter()
+            .map(|counts| {
+                let key = counts.packed().expect("Table counts fit in four bits");
+                (key, ranked_takes(enumerate_takes(&counts, rules)))
+            })
+            .collect();
+
+        Ok(TakeTable {
+            rules: *rules,
+            dices,
+            entries,
+        })
+    }
+
+    /// Table of the [`Ruleset::scoring`] of `rules`, built on first use and
+    /// shared while among the recently used rulesets; callers owning a table
+    /// from `new` roll with `Hand::roll` instead
+    pub fn for_rules(rules: &Ruleset) -> Result<Arc<Self>, KostkaError> {
+        static TABLES: RulesCache<TakeTable> = RulesCache::new(CACHED_RULESETS);
+
+        rules.validate()?;
+        TABLES.get_or_build(&rules.scoring(), TakeTable::new)
+    }
+
+    /// Table of the default rules, which are always supported
+    pub(crate) fn for_default_rules() -> Arc<Self> {
+        Self::for_rules(&Ruleset::default()).expect("Default rules are supported")
+    }
+
+    pub fn rules(&self) -> &Ruleset {
+        &self.rules
+    }
+
+    /// Takes of a hand with `dice_counts` best first, None for more dices
+    /// than the table holds
+    pub fn takes(&self, dice_counts: &FaceCounts) -> Option<&Arc<[TakeOption]>> {
+        if dice_counts.sides() != self.rules.sides {
+            return None;
+        }
+        self.entries.get(&dice_counts.packed()?)
+    }
+
+    /// Most dices of a hand the table holds the takes of
+    pub fn dices(&self) -> u32 {
+        self.dices
+    }
+
+    /// Number of multisets in the table
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Hasher of the packed counts keys, a multiply is enough for keys nobody
+/// picks to collide and is much cheaper than the default hasher on every roll
+#[derive(Default)]
+struct PackedHasher(u64);
+
+impl Hasher for PackedHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {
+        unreachable!("Only packed counts are hashed")
+    }
+
+    fn write_u64(&mut self, key: u64) {
+        let hash = key.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        self.0 = hash ^ (hash >> 32);
+    }
+}
+
+/// Number of non-empty multisets of up to `dices` dices of `sides` sides
+fn multisets(dices: u32, sides: u32) -> u64 {
+    // binomial coefficient (dices + sides, sides), less the empty multiset
+    let all = (1..=sides as u64).fold(1, |n, k| n * (dices as u64 + k) / k);
+    all - 1
+}
+
+/// Pushes every non-empty multiset of up to `left` more dices, counting
+/// the faces from index `face` on
+fn push_counts(counts: &mut FaceCounts, face: usize, left: u32, all: &mut Vec<FaceCounts>) {
+    if face == counts.len() {
+        if counts.total() > 0 {
+            all.push(*counts);
+        }
+        return;
+    }
+    for n in 0..=left {
+        counts[face] = n;
+        push_counts(counts, face + 1, left - n, all);
+    }
+    counts[face] = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice_m::{distinct_rolls, StopPoints, MAX_POINTS};
+
+    #[test]
+    fn every_multiset() {
+        let table = TakeTable::new(&Ruleset::default()).unwrap();
+
+        assert_eq!(table.len(), 923);
+    }
+
+    #[test]
+    fn same_as_enumeration() {
+        let rules = Ruleset::default();
+        let table = TakeTable::new(&rules).unwrap();
+
+        for n in 1..=6 {
+            for (dices, _) in distinct_rolls(n, 6) {
+                let mut counts = FaceCounts::new(6);
+                for dice in dices.iter() {
+                    counts[dice.value as usize - 1] += 1;
+                }
+                let takes = table.takes(&counts).unwrap();
+                assert_eq!(*takes, ranked_takes(enumerate_takes(&counts, &rules)));
+            }
+        }
+    }
+
+    #[test]
+    fn keyed_on_rules() {
+        let custom = Ruleset {
+            one: 150,
+            ..Ruleset::default()
+        };
+        let default_table = TakeTable::for_rules(&Ruleset::default()).unwrap();
+        let custom_table = TakeTable::for_rules(&custom).unwrap();
+
+        let one = FaceCounts::from([1, 0, 0, 0, 0, 0]);
+        let value = |table: &TakeTable| table.takes(&one).unwrap().iter().next().unwrap().value;
+        assert_eq!(value(&default_table), 100);
+        assert_eq!(value(&custom_table), 150);
+    }
+
+    #[test]
+    fn shared_by_the_turn_rules() {
+        let turn_rules = Ruleset {
+            min_bank: 350,
+            piggyback: true,
+            hot_dice_bonus: 500,
+            stop_points: StopPoints::new(&[1, 2]).unwrap(),
+            ..Ruleset::default()
+        };
+        let bad_bonus = Ruleset {
+            hot_dice_bonus: MAX_POINTS + 1,
+            ..Ruleset::default()
+        };
+
+        assert!(Arc::ptr_eq(
+            &TakeTable::for_rules(&turn_rules).unwrap(),
+            &TakeTable::for_default_rules()
+        ));
+        assert!(TakeTable::for_rules(&bad_bonus).is_err());
+    }
+
+    #[test]
+    fn eight_dices() {
+        let rules = Ruleset {
+            dices: 8,
+            ..Ruleset::default()
+        };
+        let table = TakeTable::new(&rules).unwrap();
+
+        assert_eq!(table.len(), 3002);
+        let takes = table.takes(&[3, 0, 0, 0, 5, 0].into()).unwrap();
+        assert_eq!(takes[0].value, 1000 + 2000);
+    }
+
+    #[test]
+    fn many_sides_stop_at_six_dices() {
+        let rules = Ruleset {
+            dices: 10,
+            sides: 12,
+            ..Ruleset::default()
+        };
+        let table = TakeTable::new(&rules).unwrap();
+
+        assert_eq!((table.dices(), table.len()), (6, 18563));
+        let triple = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3];
+        assert_eq!(table.takes(&triple.into()).unwrap()[0].value, 1200);
+        assert_eq!(
+            table.takes(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7].into()),
+            None
+        );
+    }
+
+    #[test]
+    fn refuses_unsupported_rules() {
+        let rules = Ruleset {
+            dices: 0,
+            ..Ruleset::default()
+        };
+
+        assert!(matches!(
+            TakeTable::for_rules(&rules),
+            Err(KostkaError::Ruleset(_))
+        ));
+    }
+
+    #[test]
+    fn too_many_dices() {
+        let table = TakeTable::for_rules(&Ruleset::default()).unwrap();
+
+        assert_eq!(table.takes(&[7, 0, 0, 0, 0, 0].into()), None);
+        assert_eq!(table.takes(&[2, 1, 1, 1, 1, 1].into()), None);
+        assert_eq!(table.takes(&[1, 0, 0, 0].into()), None);
+    }
+}
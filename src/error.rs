@@ -0,0 +1,70 @@
+use std::{fmt::Display, io};
+
+use crate::dice_m::{DicesError, TakeOption};
+
+/// Failures of the game engine and its players
+#[derive(Debug)]
+pub enum KostkaError {
+    /// A player picked a take its hand does not offer
+    InvalidTake(TakeOption),
+    InvalidDice(DicesError),
+    /// No player sits at the index
+    PlayerIndex(usize),
+    /// Every player forfeited, nobody can win
+    AllForfeited,
+    /// Neither a built-in nor an external bot
+    UnknownPlayer(String),
+    Io(io::Error),
+    /// Dices or sides the engine does not support
+    Ruleset(String),
+    /// The players cannot be split into the teams
+    Teams(String),
+    /// The game server refused the player
+    Refused(String),
+    /// Answered a question the game did not ask
+    NotAsked,
+    /// No game with the id is hosted
+    UnknownGame(u64),
+}
+
+impl Display for KostkaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KostkaError::InvalidTake(take) => {
+                write!(f, "take {:?} is not offered by the hand", take.dices_used)
+            }
+            KostkaError::InvalidDice(e) => write!(f, "invalid dices: {}", e),
+            KostkaError::PlayerIndex(i) => write!(f, "no player at index {}", i),
+            KostkaError::AllForfeited => write!(f, "all players forfeited"),
+            KostkaError::UnknownPlayer(kind) => write!(f, "unknown player '{}'", kind),
+            KostkaError::Io(e) => write!(f, "i/o error: {}", e),
+            KostkaError::Ruleset(e) => write!(f, "invalid ruleset: {}", e),
+            KostkaError::Teams(e) => write!(f, "invalid teams: {}", e),
+            KostkaError::Refused(e) => write!(f, "refused by the server: {}", e),
+            KostkaError::NotAsked => write!(f, "answered a question that was not asked"),
+            KostkaError::UnknownGame(id) => write!(f, "no game {}", id),
+        }
+    }
+}
+
+impl std::error::Error for KostkaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KostkaError::InvalidDice(e) => Some(e),
+            KostkaError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DicesError> for KostkaError {
+    fn from(e: DicesError) -> Self {
+        KostkaError::InvalidDice(e)
+    }
+}
+
+impl From<io::Error> for KostkaError {
+    fn from(e: io::Error) -> Self {
+        KostkaError::Io(e)
+    }
+}
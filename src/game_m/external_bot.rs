@@ -0,0 +1,298 @@
+//! Strategy running in a separate process, talking JSON lines over its
+//! stdin and stdout.
+//!
+//! The game sends one message per line, tagged by `type`:
+//!
+//! - `{"type": "new_round"}` and `{"type": "new_dices"}` expect no reply
+//! - `{"type": "pick_take", "state": ..., "dices": [1, 5, ...], "takes": [...]}`
+//!   expects `{"take": <index into takes>}` or `{"take": null}` to give up the turn
+//! - `{"type": "continue_or_stop", "state": ...}` expects
+//!   `{"action": "continue"}` or `{"action": "stop"}`
+//! - `{"type": "take_over", "state": ...}`, asked in the piggyback variant,
+//!   expects `{"take_over": true}` to start from the state's `previous_turn`
+//!   or `{"take_over": false}` to roll all the dices
+//!
+//! A reply that is late, malformed or out of range forfeits the game.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{game::GameState, Decision, GameAction};
+use crate::dice_m::{Hand, TakeOption};
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<'a> {
+    NewRound,
+    NewDices,
+    PickTake {
+        state: &'a GameState,
+        dices: Vec<i32>,
+        takes: &'a [TakeOption],
+    },
+    ContinueOrStop {
+        state: &'a GameState,
+    },
+    TakeOver {
+        state: &'a GameState,
+    },
+}
+
+#[derive(Deserialize)]
+struct PickReply {
+    take: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Continue,
+    Stop,
+}
+
+#[derive(Deserialize)]
+struct ActionReply {
+    action: Action,
+}
+
+#[derive(Deserialize)]
+struct TakeOverReply {
+    take_over: bool,
+}
+
+#[derive(Debug)]
+pub struct ExternalBot {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines read from the bot's stdout by a reader thread
+    lines: Receiver<io::Result<String>>,
+    timeout: Duration,
+    forfeited: bool,
+}
+
+impl ExternalBot {
+    /// Starts `program` with `args`, waiting at most `timeout` for each reply
+    pub fn spawn(program: &str, args: &[&str], timeout: Duration) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("Stdin is piped");
+        let stdout = child.stdout.take().expect("Stdout is piped");
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(ExternalBot {
+            child,
+            stdin,
+            lines,
+            timeout,
+            forfeited: false,
+        })
+    }
+
+    /// Starts a command line split the way a POSIX shell does, e.g.
+    /// `python3 "my bot.py"`
+    pub fn from_command_line(command: &str) -> io::Result<Self> {
+        let words = shell_words::split(command)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let (program, args) = words
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Empty command"))?;
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        Self::spawn(program, &args, DEFAULT_TIMEOUT)
+    }
+
+    fn send(&mut self, request: &Request) -> Option<()> {
+        if self.forfeited {
+            return None;
+        }
+        let mut line = serde_json::to_string(request).expect("Requests serialize");
+        line.push('\n');
+
+        let sent = self
+            .stdin
+            .write_all(line.as_bytes())
+            .and_then(|_| self.stdin.flush());
+        self.forfeit_on_none(sent.ok())
+    }
+
+    fn receive<T: for<'de> Deserialize<'de>>(&mut self) -> Option<T> {
+        let reply = match self.lines.recv_timeout(self.timeout) {
+            Ok(Ok(line)) => serde_json::from_str(&line).ok(),
+            _ => None, // timeout, closed stdout or read error
+        };
+        self.forfeit_on_none(reply)
+    }
+
+    fn forfeit_on_none<T>(&mut self, value: Option<T>) -> Option<T> {
+        if value.is_none() {
+            self.forfeited = true;
+        }
+        value
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Decision for ExternalBot {
+    fn pick_take(&mut self, game_state: &GameState, hand: Hand) -> Option<TakeOption> {
+        // stable order so the bot's index means the same take every time
+        let takes = hand.takes();
+
+        let request = Request::PickTake {
+            state: game_state,
+            dices: hand.get_dices().iter().map(|dice| dice.value).collect(),
+            takes,
+        };
+        self.send(&request)?;
+        let reply: PickReply = self.receive()?;
+
+        match reply.take {
+            Some(i) => {
+                let take = takes.get(i).copied();
+                self.forfeit_on_none(take)
+            }
+            None => None,
+        }
+    }
+
+    fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction {
+        let reply = self
+            .send(&Request::ContinueOrStop { state: game_state })
+            .and_then(|_| self.receive::<ActionReply>());
+
+        match reply {
+            Some(ActionReply {
+                action: Action::Continue,
+            }) => GameAction::Continue,
+            _ => GameAction::Stop,
+        }
+    }
+
+    fn take_over(&mut self, game_state: &GameState) -> bool {
+        let reply = self
+            .send(&Request::TakeOver { state: game_state })
+            .and_then(|_| self.receive::<TakeOverReply>());
+
+        reply.is_some_and(|reply| reply.take_over)
+    }
+
+    fn new_round(&mut self) {
+        let _ = self.send(&Request::NewRound);
+    }
+
+    fn new_dices(&mut self) {
+        let _ = self.send(&Request::NewDices);
+    }
+
+    fn forfeited(&self) -> bool {
+        self.forfeited
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::dice_m::Dices;
+
+    fn script_bot(script: &str) -> ExternalBot {
+        ExternalBot::spawn("sh", &["-c", script], Duration::from_millis(500)).unwrap()
+    }
+
+    fn hand() -> Hand {
+        Hand::from_dices(Dices::from([1, 1, 3, 4, 4, 5])).unwrap()
+    }
+
+    #[test]
+    fn picks_by_index() {
+        let mut bot = script_bot(
+            r#"while read line; do
+                case "$line" in
+                    *pick_take*) echo '{"take": 0}' ;;
+                    *continue_or_stop*) echo '{"action": "continue"}' ;;
+                    *take_over*) echo '{"take_over": true}' ;;
+                esac
+            done"#,
+        );
+        let state = GameState::new();
+
+        bot.new_round();
+        let take = bot.pick_take(&state, hand()).unwrap();
+        assert_eq!(take.value, 250); // best take first
+
+        assert_eq!(bot.continue_or_stop(&state), GameAction::Continue);
+        assert!(bot.take_over(&state));
+        assert!(!bot.forfeited());
+    }
+
+    #[test]
+    fn quoted_arguments() {
+        let mut bot = ExternalBot::from_command_line(
+            r#"sh -c 'while read line; do echo "{\"action\": \"continue\"}"; done'"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            bot.continue_or_stop(&GameState::new()),
+            GameAction::Continue
+        );
+        assert!(ExternalBot::from_command_line("sh -c 'unclosed").is_err());
+        assert!(ExternalBot::from_command_line("  ").is_err());
+    }
+
+    #[test]
+    fn timeout_forfeits() {
+        let mut bot = script_bot("sleep 5");
+
+        assert_eq!(bot.pick_take(&GameState::new(), hand()), None);
+        assert!(bot.forfeited());
+    }
+
+    #[test]
+    fn garbage_forfeits() {
+        let mut bot = script_bot("while read line; do echo 'take 0'; done");
+
+        assert_eq!(bot.continue_or_stop(&GameState::new()), GameAction::Stop);
+        assert!(bot.forfeited());
+    }
+
+    #[test]
+    fn bad_take_over_forfeits() {
+        let mut bot = script_bot(r#"while read line; do echo '{"take_over": "yes"}'; done"#);
+
+        assert!(!bot.take_over(&GameState::new()));
+        assert!(bot.forfeited());
+    }
+
+    #[test]
+    fn index_out_of_range_forfeits() {
+        let mut bot = script_bot(r#"while read line; do echo '{"take": 99}'; done"#);
+
+        assert_eq!(bot.pick_take(&GameState::new(), hand()), None);
+        assert!(bot.forfeited());
+    }
+}
@@ -1,57 +1,233 @@
-use std::vec;
+use std::{cmp::Reverse, vec};
 
-use super::Player;
-use crate::{dice_m::Hand, game_m::GameAction};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
 
+use super::{
+    game_match::{Match, MatchResult, Step},
+    Player,
+};
+use crate::{dice_m::Ruleset, KostkaError};
+
+/// Standing of the teams, a team being a single player without teams
 pub enum GameStatus {
     Winning(u16), // todo vec?
     Won(u16),
     NobodyWinning,
-    Error(&'static str),
 }
 
+/// Scoreboard entry of one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerInfo {
+    /// Points the player banked, see [`GameState::team_scores`] for teams
+    pub score: u32,
+    /// The player's team, their own index without teams
+    pub team: usize,
+    /// Banked a turn at least once
+    pub entered: bool,
+    /// Busted turns in a row
+    pub bust_streak: u32,
+    pub forfeited: bool,
+}
+
+/// State of the game as seen by the players
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameState {
     round: u16,
-    players_count: u16,
     playing: u16,
     score_goal: u32,
     player_scores: Vec<u32>,
-    bad_state: bool,
+    /// Points taken so far in the current turn
+    turn_score: u32,
+    /// Dices the current player rolls next
+    dices_available: usize,
+    /// Dices rolled at the start of a turn and after hot dice
+    all_dices: usize,
+    /// Times all the dices scored in the current turn
+    hot_dices: u32,
+    /// Turn score and dices left of the previous turn, if it was banked
+    previous_turn: Option<(u32, usize)>,
+    entered: Vec<bool>,
+    bust_streaks: Vec<u32>,
+    /// Players out of the game, their turns are skipped
+    forfeited: Vec<bool>,
+    /// Number of teams sharing their scores, player `i` plays for team
+    /// `i % teams` so the turns alternate between them; 0 without teams
+    teams: usize,
 }
 
 impl GameState {
     pub fn new() -> Self {
-        GameState {
-            round: 0,
-            players_count: 0,
-            playing: 0,
-            score_goal: 0,
-            player_scores: vec![],
-            bad_state: false,
-        }
+        Self::with_goal(0)
     }
 
     pub fn with_goal(score_goal: u32) -> Self {
         GameState {
             round: 0,
-            players_count: 0,
             playing: 0,
             score_goal,
             player_scores: vec![],
-            bad_state: false,
+            turn_score: 0,
+            dices_available: 6,
+            all_dices: 6,
+            hot_dices: 0,
+            previous_turn: None,
+            entered: vec![],
+            bust_streaks: vec![],
+            forfeited: vec![],
+            teams: 0,
+        }
+    }
+
+    /// Index of the player on turn, i.e. the asked player's own index
+    pub fn my_index(&self) -> usize {
+        self.playing as usize
+    }
+
+    /// Points banked by the player on turn, 0 before any player joined
+    pub fn my_score(&self) -> u32 {
+        self.player_scores
+            .get(self.my_index())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Points banked by every player, see [`GameState::team_scores`] for teams
+    pub fn scores(&self) -> &[u32] {
+        &self.player_scores
+    }
+
+    /// Whether the players share their scores in teams
+    pub fn in_teams(&self) -> bool {
+        self.teams > 0
+    }
+
+    /// Number of teams, every player being a team of their own without teams
+    pub fn teams_count(&self) -> usize {
+        match self.teams {
+            0 => self.players_count(),
+            teams => teams,
+        }
+    }
+
+    pub fn team_of(&self, player: usize) -> usize {
+        match self.teams {
+            0 => player,
+            teams => player % teams,
         }
     }
 
-    pub fn set_goal(&mut self, score_goal: u32) {
+    /// Points banked by the players of every team together
+    pub fn team_scores(&self) -> Vec<u32> {
+        let mut scores = vec![0; self.teams_count()];
+        for (player, score) in self.player_scores.iter().enumerate() {
+            scores[self.team_of(player)] += score;
+        }
+        scores
+    }
+
+    /// Points of the team on turn, 0 before any player joined
+    pub fn my_team_score(&self) -> u32 {
+        self.team_scores()
+            .get(self.team_of(self.my_index()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn goal(&self) -> u32 {
+        self.score_goal
+    }
+
+    /// Number of finished rounds
+    pub fn round(&self) -> u16 {
+        self.round
+    }
+
+    pub fn players_count(&self) -> usize {
+        self.player_scores.len()
+    }
+
+    pub fn turn_score(&self) -> u32 {
+        self.turn_score
+    }
+
+    pub fn dices_available(&self) -> usize {
+        self.dices_available
+    }
+
+    pub fn all_dices(&self) -> usize {
+        self.all_dices
+    }
+
+    /// Times all the dices scored in the current turn
+    pub fn hot_dices(&self) -> u32 {
+        self.hot_dices
+    }
+
+    /// Turn score banked by the previous player and the dices they left,
+    /// which the player on turn may take over in the piggyback variant
+    pub fn previous_turn(&self) -> Option<(u32, usize)> {
+        self.previous_turn
+    }
+
+    pub fn player(&self, index: usize) -> Option<PlayerInfo> {
+        Some(PlayerInfo {
+            score: *self.player_scores.get(index)?,
+            team: self.team_of(index),
+            entered: self.entered[index],
+            bust_streak: self.bust_streaks[index],
+            forfeited: self.forfeited[index],
+        })
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = PlayerInfo> + '_ {
+        (0..self.players_count()).filter_map(|i| self.player(i))
+    }
+
+    pub(crate) fn set_goal(&mut self, score_goal: u32) {
         self.score_goal = score_goal;
     }
 
-    pub fn add_player(&mut self) {
-        self.players_count += 1;
+    /// Splits the players into `teams` of at least two players each
+    pub(crate) fn set_teams(&mut self, teams: usize) -> Result<(), KostkaError> {
+        let players = self.players_count();
+        if teams < 2 || !players.is_multiple_of(teams) || players / teams < 2 {
+            return Err(KostkaError::Teams(format!(
+                "{} players in {} teams, expected teams of two or more players of the same size",
+                players, teams
+            )));
+        }
+        self.teams = teams;
+        Ok(())
+    }
+
+    pub(crate) fn add_player(&mut self) {
         self.player_scores.push(0); // todo maybe create vector on game start
+        self.entered.push(false);
+        self.bust_streaks.push(0);
+        self.forfeited.push(false);
+    }
+
+    /// Sets the dices of a full throw, the next turn starts with them
+    pub(crate) fn set_all_dices(&mut self, all_dices: usize) {
+        self.all_dices = all_dices;
+        self.dices_available = all_dices;
+    }
+
+    pub(crate) fn set_turn(&mut self, turn_score: u32, dices_available: usize) {
+        self.turn_score = turn_score;
+        self.dices_available = dices_available;
+    }
+
+    pub(crate) fn add_hot_dice(&mut self) {
+        self.hot_dices += 1;
     }
 
-    pub fn add_score_to_current(&mut self, score: u32) {
+    pub(crate) fn set_forfeited(&mut self, player: usize) {
+        self.forfeited[player] = true;
+    }
+
+    pub(crate) fn add_score_to_current(&mut self, score: u32) {
         let current_index = self.playing;
         let get = self.player_scores.get_mut(current_index as usize);
 
@@ -62,16 +238,25 @@ impl GameState {
     }
 
     pub fn update_player_turn(&mut self, turn: TurnResult) {
+        let current = self.playing as usize;
+        self.previous_turn = match turn {
+            TurnResult::Value(v) => Some((v, self.dices_available)),
+            TurnResult::Nothing | TurnResult::Forfeit => None,
+        };
         match turn {
-            TurnResult::Error(_) => todo!(),
-            TurnResult::Nothing => {}
+            TurnResult::Nothing => self.bust_streaks[current] += 1,
+            TurnResult::Forfeit => self.forfeited[current] = true,
             TurnResult::Value(v) => {
                 self.add_score_to_current(v);
+                self.entered[current] = true;
+                self.bust_streaks[current] = 0;
             }
         };
+        self.set_turn(0, self.all_dices);
+        self.hot_dices = 0;
 
         // next players move
-        self.playing = if self.playing == self.players_count - 1 {
+        self.playing = if self.playing as usize == self.players_count() - 1 {
             self.round += 1;
             0
         } else {
@@ -79,137 +264,355 @@ impl GameState {
         };
     }
 
-    pub fn game_status(&self) -> GameStatus {
-        let who_winning: Vec<_> = self
-            .player_scores
-            .iter()
-            .enumerate()
-            .filter(|(i, &score)| score > self.score_goal)
-            .collect();
-
-        if self.bad_state {
-            return GameStatus::Error("In bad state"); // todo hold error message
+    pub fn game_status(&self) -> Result<GameStatus, KostkaError> {
+        let team_scores = self.team_scores();
+        // a team plays on while any of its players does
+        let mut active = team_scores.iter().enumerate().filter(|&(team, _)| {
+            (0..self.players_count()).any(|i| self.team_of(i) == team && !self.forfeited[i])
+        });
+
+        // highest score over the goal, earlier team on a tie
+        let leader = active
+            .clone()
+            .filter(|(_, &score)| score > self.score_goal)
+            .max_by_key(|&(i, &score)| (score, Reverse(i)));
+
+        match (active.next(), active.next()) {
+            (None, _) => return Err(KostkaError::AllForfeited),
+            (Some((i, _)), None) if self.teams_count() > 1 => return Ok(GameStatus::Won(i as u16)),
+            _ => {}
         }
 
-        match who_winning.as_slice() {
-            [] => GameStatus::NobodyWinning,
-            [el] => GameStatus::Winning(el.0 as u16),
-            other => todo!(),
-        }
+        Ok(match leader {
+            None => GameStatus::NobodyWinning,
+            // the round is finished so everybody had the same number of turns
+            Some((i, _)) if self.playing == 0 => GameStatus::Won(i as u16),
+            Some((i, _)) => GameStatus::Winning(i as u16),
+        })
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 pub struct Game {
     players: Vec<Player>,
-    state: GameState,
+    game: Match,
 }
 
-enum TurnResult {
-    Error(&'static str),
+/// How a turn ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnResult {
     Nothing,
+    /// Player left the game
+    Forfeit,
     Value(u32),
 }
 
-pub enum MatchResult {
-    Won(u16),
-    Error(&'static str),
+#[cfg(feature = "native")]
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
+    #[cfg(feature = "native")]
     pub fn new() -> Game {
-        let game_state = GameState::new();
+        Self::with_match(Match::new())
+    }
+
+    /// Game with reproducible dice rolls
+    pub fn with_seed(seed: u64) -> Game {
+        Self::with_match(Match::with_seed(seed))
+    }
+
+    /// Game rolling from any generator, see [`Match::with_rng`]
+    pub fn with_rng<R: RngCore + Send + Sync + 'static>(rng: R) -> Game {
+        Self::with_match(Match::with_rng(rng))
+    }
+
+    fn with_match(game: Match) -> Game {
         Game {
             players: vec![],
-            state: game_state,
+            game,
         }
     }
 
-    pub fn add_player(&mut self, player: Player) {
+    /// Seats another player, errs once the teams are set
+    pub fn add_player(&mut self, player: Player) -> Result<(), KostkaError> {
+        self.game.add_player()?;
         self.players.push(player);
-        self.state.add_player();
+        Ok(())
     }
 
     pub fn set_limit(&mut self, limit: u32) {
-        self.state.set_goal(limit);
+        self.game.set_limit(limit);
     }
 
-    pub fn play(&mut self) -> MatchResult {
-        let res = loop {
-            // play a turn
-            let res = self.play_player();
+    /// Plays by `rules`, errs when their dices or sides are not supported
+    pub fn set_rules(&mut self, rules: Ruleset) -> Result<(), KostkaError> {
+        self.game.set_rules(rules)
+    }
 
-            // update state
-            self.state.update_player_turn(res);
+    /// Splits the players added so far into `teams`, see [`Match::set_teams`]
+    pub fn set_teams(&mut self, teams: usize) -> Result<(), KostkaError> {
+        self.game.set_teams(teams)
+    }
+
+    pub fn rules(&self) -> &Ruleset {
+        self.game.rules()
+    }
 
-            match self.state.game_status() {
-                GameStatus::Winning(_) => continue,
-                GameStatus::Won(i) => break MatchResult::Won(i),
-                GameStatus::NobodyWinning => continue,
-                GameStatus::Error(e) => break MatchResult::Error(e),
+    pub fn state(&self) -> &GameState {
+        self.game.state()
+    }
+
+    /// Number of finished rounds
+    pub fn rounds(&self) -> u16 {
+        self.state().round()
+    }
+
+    pub fn scores(&self) -> &[u32] {
+        self.state().scores()
+    }
+
+    /// Plays the match to the end, asking the players
+    pub fn play(&mut self) -> Result<MatchResult, KostkaError> {
+        loop {
+            let step = self.game.step()?;
+            let playing = self.game.state().my_index();
+            let player = self
+                .players
+                .get_mut(playing)
+                .ok_or(KostkaError::PlayerIndex(playing))?;
+
+            match step {
+                Step::TurnStarted(_) => player.new_round(),
+                Step::HotDice(_) => player.new_dices(),
+                Step::PickTake(hand) => {
+                    let take = player.pick_take(self.game.state(), hand);
+                    match player.forfeited() {
+                        true => self.game.forfeit(playing)?,
+                        false => self.game.take(take)?,
+                    }
+                }
+                Step::TakeOver => {
+                    let take_over = player.take_over(self.game.state());
+                    match player.forfeited() {
+                        true => self.game.forfeit(playing)?,
+                        false => self.game.take_over(take_over)?,
+                    }
+                }
+                Step::ContinueOrStop => {
+                    let action = player.continue_or_stop(self.game.state());
+                    match player.forfeited() {
+                        true => self.game.forfeit(playing)?,
+                        false => self.game.decide(action)?,
+                    }
+                }
+                Step::TurnEnded(..) => {}
+                Step::Finished(result) => return Ok(result),
             }
-        };
+        }
+    }
+}
 
-        res
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dice_m::{Hand, TakeOption},
+        game_m::GameAction,
+    };
+
+    fn two_players() -> GameState {
+        let mut state = GameState::with_goal(1000);
+        state.add_player();
+        state.add_player();
+        state
     }
 
-    // todo refactor
-    fn play_player(&mut self) -> TurnResult {
-        let player_index = self.state.playing;
-        let player_option: Option<&mut Player> = self.players.get_mut(player_index as usize);
-        let player = if let Some(pl) = player_option {
-            pl
-        } else {
-            return TurnResult::Error("Wrong player index");
-        };
+    #[test]
+    fn scoreboard() {
+        let mut state = two_players();
+
+        state.update_player_turn(TurnResult::Value(300));
+        state.update_player_turn(TurnResult::Nothing);
+        state.update_player_turn(TurnResult::Nothing);
+        state.update_player_turn(TurnResult::Nothing);
+
+        assert_eq!(state.round(), 2);
+        assert_eq!(state.scores(), &[300, 0]);
+        assert_eq!(
+            state.player(0),
+            Some(PlayerInfo {
+                score: 300,
+                team: 0,
+                entered: true,
+                bust_streak: 1,
+                forfeited: false,
+            })
+        );
+        assert_eq!(state.player(1).map(|p| p.bust_streak), Some(2));
+        assert_eq!(state.player(2), None);
+    }
 
-        let mut score = 0;
-        let mut dices_available = 6;
+    #[test]
+    fn no_players_score_nothing() {
+        let state = GameState::new();
 
-        player.new_round();
+        assert_eq!(state.my_score(), 0);
+        assert_eq!(state.my_team_score(), 0);
+    }
 
-        // todo fix print
-        // let name = player.get_name();
-        // let current_score = ;
-        // println!("Playing: {} - score {}", name, current_score);
+    #[test]
+    fn teams_share_their_scores() {
+        let mut state = GameState::with_goal(1000);
+        for _ in 0..4 {
+            state.add_player();
+        }
+        state.set_teams(2).unwrap();
 
-        loop {
-            let hand = Hand::with_dices(dices_available);
+        state.update_player_turn(TurnResult::Value(600));
+        state.update_player_turn(TurnResult::Value(300));
+        state.update_player_turn(TurnResult::Value(500));
 
-            print!("score: {} | dices: ", score);
+        assert_eq!(state.team_scores(), [1100, 300]);
+        assert_eq!(state.player(2).map(|p| p.team), Some(0));
+        assert_eq!(state.my_team_score(), 300);
+        // the other team still gets its turn in the round
+        assert!(matches!(state.game_status(), Ok(GameStatus::Winning(0))));
 
-            for dice in hand.get_dices() {
-                print!(" {}", dice);
-            }
-            println!();
+        state.update_player_turn(TurnResult::Nothing);
+        assert!(matches!(state.game_status(), Ok(GameStatus::Won(0))));
+    }
 
-            let take = player.pick_take(&self.state, hand);
+    #[test]
+    fn teams_need_two_players_each() {
+        let mut state = two_players();
+        assert!(matches!(state.set_teams(2), Err(KostkaError::Teams(_))));
 
-            // todo check if take is from hand, do not trust player
+        state.add_player();
+        assert!(matches!(state.set_teams(2), Err(KostkaError::Teams(_))));
+        assert!(!state.in_teams());
+        assert_eq!(state.teams_count(), 3);
+    }
 
-            match take {
-                Some(take) => {
-                    score += take.value;
-                    dices_available -= take.dices_count();
-                }
-                None => return TurnResult::Nothing, // no move possible
-            };
+    #[test]
+    fn turn_resets() {
+        let mut state = two_players();
+        state.set_turn(450, 2);
 
-            println!("score: {}", score);
+        assert_eq!(state.turn_score(), 450);
+        assert_eq!(state.dices_available(), 2);
 
-            match dices_available {
-                0 => {
-                    dices_available = 6;
-                    player.new_dices()
-                }
-                1 | 2 => {
-                    if player.continue_or_stop(&self.state) == GameAction::Stop {
-                        break;
-                    }
-                }
-                _ => {}
-            };
+        state.update_player_turn(TurnResult::Value(450));
+
+        assert_eq!(state.my_index(), 1);
+        assert_eq!(state.turn_score(), 0);
+        assert_eq!(state.dices_available(), 6);
+        assert_eq!(state.previous_turn(), Some((450, 2)));
+
+        state.update_player_turn(TurnResult::Nothing);
+        assert_eq!(state.previous_turn(), None);
+    }
+
+    #[test]
+    fn turn_starts_with_all_dices() {
+        let mut state = two_players();
+        state.set_all_dices(5);
+        state.set_turn(100, 1);
+
+        state.update_player_turn(TurnResult::Value(100));
+
+        assert_eq!(state.dices_available(), 5);
+    }
+
+    #[test]
+    fn eight_four_sided_dices() {
+        let rules = Ruleset {
+            dices: 8,
+            sides: 4,
+            ..Ruleset::default()
+        };
+        let mut game = Game::with_seed(1);
+        game.set_rules(rules).unwrap();
+        game.set_limit(2000);
+        for name in ["a", "b"] {
+            game.add_player(Player::bot_with_rules("optimal", name, &rules).unwrap())
+                .unwrap();
         }
 
-        TurnResult::Value(score)
+        assert!(matches!(game.play(), Ok(MatchResult::Won(_))));
+        assert_eq!(game.state().all_dices(), 8);
+    }
+
+    #[test]
+    fn twelve_sided_dices() {
+        let rules = Ruleset {
+            sides: 12,
+            ..Ruleset::default()
+        };
+        let mut game = Game::with_seed(2);
+        game.set_rules(rules).unwrap();
+        game.set_limit(2000);
+        for name in ["a", "b"] {
+            game.add_player(Player::bot_with_rules("greedy", name, &rules).unwrap())
+                .unwrap();
+        }
+
+        assert!(matches!(game.play(), Ok(MatchResult::Won(_))));
+    }
+
+    /// Sets aside six ones whatever it rolls
+    struct Cheater;
+
+    impl crate::game_m::Decision for Cheater {
+        fn pick_take(&mut self, _game_state: &GameState, _hand: Hand) -> Option<TakeOption> {
+            Some(TakeOption {
+                dices_used: [6, 0, 0, 0, 0, 0].into(),
+                value: 8000,
+            })
+        }
+
+        fn continue_or_stop(&mut self, _game_state: &GameState) -> GameAction {
+            GameAction::Stop
+        }
+
+        fn new_round(&mut self) {}
+
+        fn new_dices(&mut self) {}
+    }
+
+    #[test]
+    fn rejects_takes_not_offered() {
+        let mut game = Game::with_seed(0);
+        game.add_player(Player::new("cheater", Box::new(Cheater)))
+            .unwrap();
+
+        assert!(matches!(game.play(), Err(KostkaError::InvalidTake(_))));
+    }
+
+    #[test]
+    fn rejects_unsupported_rules() {
+        let mut game = Game::with_seed(0);
+        let rules = Ruleset {
+            sides: 13,
+            ..Ruleset::default()
+        };
+
+        assert!(matches!(
+            game.set_rules(rules),
+            Err(KostkaError::Ruleset(_))
+        ));
+        assert!(matches!(
+            Player::bot("nobody", "p1"),
+            Err(KostkaError::UnknownPlayer(_))
+        ));
     }
 }
@@ -0,0 +1,590 @@
+use std::{fmt, sync::Arc};
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    game::{GameStatus, TurnResult},
+    GameAction, GameState,
+};
+use crate::{
+    dice_m::{Hand, Ruleset, TakeOption, TakeTable},
+    KostkaError,
+};
+
+/// What the match does on the next step
+#[derive(Debug)]
+enum Phase {
+    TurnStart,
+    /// Waits for [`Match::take_over`]
+    TakingOver,
+    Roll,
+    /// Waits for [`Match::take`]
+    Picking(Hand),
+    /// Waits for [`Match::decide`]
+    Deciding,
+    HotDice,
+    TurnEnd(TurnResult),
+    Over(MatchResult),
+}
+
+/// Outcome of a finished match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchResult {
+    /// Index of the winning player
+    Won(u16),
+    /// Index of the winning team when playing in teams, see
+    /// [`Match::set_teams`]
+    TeamWon(u16),
+}
+
+/// What happened on a [`Match::step`] or what the player on turn is asked
+#[derive(Debug, Clone)]
+pub enum Step {
+    /// The player starts a turn
+    TurnStarted(usize),
+    /// The player on turn may start from [`GameState::previous_turn`],
+    /// asked in the piggyback variant, answer with [`Match::take_over`]
+    TakeOver,
+    /// The player on turn picks a take of the hand, answer with [`Match::take`]
+    PickTake(Hand),
+    /// The player on turn may bank the turn score, asked after every take
+    /// the stop points and the minimum bank of the rules allow, answer with
+    /// [`Match::decide`]
+    ContinueOrStop,
+    /// All the dices scored, the player on turn gets all of them again;
+    /// the number of times it happened this turn
+    HotDice(u32),
+    /// The player's turn ended
+    TurnEnded(usize, TurnResult),
+    Finished(MatchResult),
+}
+
+/// Dice generator of a match
+struct MatchRng(Box<dyn RngCore + Send + Sync>);
+
+impl fmt::Debug for MatchRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MatchRng")
+    }
+}
+
+/// Game engine driven one step at a time, with no players attached.
+///
+/// Every [`Match::step`] advances the game to the next event or question;
+/// a question is returned again until it is answered, so the players may
+/// live anywhere, e.g. behind a network connection.
+#[derive(Debug)]
+pub struct Match {
+    state: GameState,
+    rules: Ruleset,
+    takes: Arc<TakeTable>,
+    rng: MatchRng,
+    phase: Phase,
+}
+
+#[cfg(feature = "native")]
+impl Default for Match {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Match {
+    /// Match rolling from a generator seeded by the OS
+    #[cfg(feature = "native")]
+    pub fn new() -> Self {
+        Self::with_rng(ChaCha8Rng::from_entropy())
+    }
+
+    /// Match with reproducible dice rolls
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Match rolling from any generator, e.g. a seeded [`ChaCha8Rng`] or a
+    /// recorded sequence of rolls
+    pub fn with_rng<R: RngCore + Send + Sync + 'static>(rng: R) -> Self {
+        Match {
+            state: GameState::new(),
+            rules: Ruleset::default(),
+            takes: TakeTable::for_default_rules(),
+            rng: MatchRng(Box::new(rng)),
+            phase: Phase::TurnStart,
+        }
+    }
+
+    /// Seats another player, errs once the teams are set
+    pub fn add_player(&mut self) -> Result<(), KostkaError> {
+        if self.state.in_teams() {
+            return Err(KostkaError::Teams(
+                "players join before the teams are set".into(),
+            ));
+        }
+        self.state.add_player();
+        Ok(())
+    }
+
+    pub fn set_limit(&mut self, limit: u32) {
+        self.state.set_goal(limit);
+    }
+
+    /// Plays by `rules`, errs when their dices or sides are not supported
+    pub fn set_rules(&mut self, rules: Ruleset) -> Result<(), KostkaError> {
+        self.takes = TakeTable::for_rules(&rules)?;
+        self.rules = rules;
+        self.state.set_all_dices(rules.dices);
+        Ok(())
+    }
+
+    /// Splits the players added so far into `teams` of the same size, two
+    /// players or more each, sharing their scores. Player `i` plays for team
+    /// `i % teams`, so the turns alternate between the teams. No player
+    /// joins afterwards and the match ends with [`MatchResult::TeamWon`].
+    pub fn set_teams(&mut self, teams: usize) -> Result<(), KostkaError> {
+        self.state.set_teams(teams)
+    }
+
+    pub fn rules(&self) -> &Ruleset {
+        &self.rules
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Result once the match is over
+    pub fn result(&self) -> Option<MatchResult> {
+        match self.phase {
+            Phase::Over(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// Hand of the [`Step::PickTake`] waiting for an answer, if any
+    pub fn asked_hand(&self) -> Option<&Hand> {
+        match &self.phase {
+            Phase::Picking(hand) => Some(hand),
+            _ => None,
+        }
+    }
+
+    /// Advances to the next event, or returns the question the player on
+    /// turn has not answered yet
+    pub fn step(&mut self) -> Result<Step, KostkaError> {
+        loop {
+            let playing = self.state.my_index();
+            match &self.phase {
+                Phase::TurnStart => {
+                    let player = self
+                        .state
+                        .player(playing)
+                        .ok_or(KostkaError::PlayerIndex(playing))?;
+                    if player.forfeited {
+                        // skipped without telling anybody
+                        self.end_turn(TurnResult::Forfeit)?;
+                        continue;
+                    }
+                    let offered = self.rules.piggyback && self.state.previous_turn().is_some();
+                    self.phase = match offered {
+                        true => Phase::TakingOver,
+                        false => Phase::Roll,
+                    };
+                    return Ok(Step::TurnStarted(playing));
+                }
+                Phase::TakingOver => return Ok(Step::TakeOver),
+                Phase::Roll => {
+                    let dices = self.state.dices_available();
+                    let hand = Hand::roll(dices, &self.takes, &mut *self.rng.0);
+                    self.phase = Phase::Picking(hand.clone());
+                    return Ok(Step::PickTake(hand));
+                }
+                Phase::Picking(hand) => return Ok(Step::PickTake(hand.clone())),
+                Phase::Deciding => return Ok(Step::ContinueOrStop),
+                Phase::HotDice => {
+                    self.phase = match self.may_stop(0) {
+                        true => Phase::Deciding,
+                        false => Phase::Roll,
+                    };
+                    return Ok(Step::HotDice(self.state.hot_dices()));
+                }
+                &Phase::TurnEnd(result) => {
+                    self.end_turn(result)?;
+                    return Ok(Step::TurnEnded(playing, result));
+                }
+                &Phase::Over(result) => return Ok(Step::Finished(result)),
+            }
+        }
+    }
+
+    /// Answers [`Step::PickTake`], None gives up the turn.
+    ///
+    /// Errs on takes the hand does not offer and when no take was asked for.
+    pub fn take(&mut self, take: Option<TakeOption>) -> Result<(), KostkaError> {
+        let Phase::Picking(hand) = &self.phase else {
+            return Err(KostkaError::NotAsked);
+        };
+        let Some(take) = take else {
+            self.phase = Phase::TurnEnd(TurnResult::Nothing);
+            return Ok(());
+        };
+        // do not trust the player to pick from the hand
+        if !hand.takes().contains(&take) {
+            return Err(KostkaError::InvalidTake(take));
+        }
+
+        let score = self.state.turn_score() + take.value;
+        let dices_left = self.state.dices_available() - take.dices_count();
+
+        // hot dices, all of them can be rolled again
+        if dices_left == 0 {
+            self.state
+                .set_turn(score + self.rules.hot_dice_bonus, self.rules.dices);
+            self.state.add_hot_dice();
+            self.phase = Phase::HotDice;
+            return Ok(());
+        }
+        self.state.set_turn(score, dices_left);
+        self.phase = match self.may_stop(dices_left) {
+            true => Phase::Deciding,
+            false => Phase::Roll,
+        };
+        Ok(())
+    }
+
+    /// Answers [`Step::TakeOver`], errs when it was not asked
+    pub fn take_over(&mut self, take_over: bool) -> Result<(), KostkaError> {
+        if !matches!(self.phase, Phase::TakingOver) {
+            return Err(KostkaError::NotAsked);
+        }
+        if let (true, Some((score, dices))) = (take_over, self.state.previous_turn()) {
+            self.state.set_turn(score, dices);
+        }
+        self.phase = Phase::Roll;
+        Ok(())
+    }
+
+    /// Answers [`Step::ContinueOrStop`], errs when it was not asked
+    pub fn decide(&mut self, action: GameAction) -> Result<(), KostkaError> {
+        if !matches!(self.phase, Phase::Deciding) {
+            return Err(KostkaError::NotAsked);
+        }
+        self.phase = match action {
+            GameAction::Continue => Phase::Roll,
+            GameAction::Stop => Phase::TurnEnd(TurnResult::Value(self.state.turn_score())),
+        };
+        Ok(())
+    }
+
+    /// Takes `player` out of the match, ending the turn if they are on it
+    pub fn forfeit(&mut self, player: usize) -> Result<(), KostkaError> {
+        if player >= self.state.players_count() {
+            return Err(KostkaError::PlayerIndex(player));
+        }
+        if matches!(self.phase, Phase::Over(_)) {
+            return Ok(());
+        }
+        if player == self.state.my_index() {
+            self.phase = Phase::TurnEnd(TurnResult::Forfeit);
+        } else {
+            self.state.set_forfeited(player);
+        }
+        Ok(())
+    }
+
+    /// Whether the player may bank the turn score with `dices_left`
+    fn may_stop(&self, dices_left: usize) -> bool {
+        self.rules.stop_points.contains(dices_left)
+            && self.state.turn_score() >= self.rules.min_bank
+    }
+
+    fn end_turn(&mut self, result: TurnResult) -> Result<(), KostkaError> {
+        self.state.update_player_turn(result);
+        self.phase = match self.state.game_status()? {
+            GameStatus::Won(i) if self.state.in_teams() => Phase::Over(MatchResult::TeamWon(i)),
+            GameStatus::Won(i) => Phase::Over(MatchResult::Won(i)),
+            GameStatus::Winning(_) | GameStatus::NobodyWinning => Phase::TurnStart,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice_m::StopPoints;
+
+    fn two_players() -> Match {
+        let mut game = Match::with_seed(7);
+        game.add_player().unwrap();
+        game.add_player().unwrap();
+        game.set_limit(300);
+        game
+    }
+
+    #[test]
+    fn questions_repeat_until_answered() {
+        let mut game = two_players();
+
+        assert!(matches!(game.step(), Ok(Step::TurnStarted(0))));
+        assert!(game.asked_hand().is_none());
+        let Ok(Step::PickTake(hand)) = game.step() else {
+            panic!("Not asked to pick");
+        };
+        let Ok(Step::PickTake(again)) = game.step() else {
+            panic!("Not asked again");
+        };
+        assert_eq!(hand.get_dices(), again.get_dices());
+        assert_eq!(
+            game.asked_hand().map(Hand::get_dices),
+            Some(hand.get_dices())
+        );
+        assert!(matches!(
+            game.decide(GameAction::Stop),
+            Err(KostkaError::NotAsked)
+        ));
+
+        game.take(None).unwrap();
+        assert!(matches!(
+            game.step(),
+            Ok(Step::TurnEnded(0, TurnResult::Nothing))
+        ));
+        assert!(matches!(game.step(), Ok(Step::TurnStarted(1))));
+    }
+
+    #[test]
+    fn rolls_from_any_generator() {
+        // a generator stuck at zero rolls only ones
+        let mut game = Match::with_rng(rand::rngs::mock::StepRng::new(0, 0));
+        game.add_player().unwrap();
+        game.step().unwrap();
+
+        let Ok(Step::PickTake(hand)) = game.step() else {
+            panic!("Not asked to pick");
+        };
+        assert!(hand.get_dices().iter().all(|dice| dice.value == 1));
+    }
+
+    #[test]
+    fn rejects_takes_not_offered() {
+        let mut game = two_players();
+        game.step().unwrap();
+        game.step().unwrap();
+
+        let cheat = TakeOption {
+            dices_used: [6, 0, 0, 0, 0, 0].into(),
+            value: 8000,
+        };
+        assert!(matches!(
+            game.take(Some(cheat)),
+            Err(KostkaError::InvalidTake(_))
+        ));
+        // still waiting for a real answer
+        assert!(matches!(game.step(), Ok(Step::PickTake(_))));
+    }
+
+    #[test]
+    fn plays_to_the_end() {
+        let mut game = two_players();
+
+        let result = loop {
+            match game.step().unwrap() {
+                Step::PickTake(hand) => game.take(hand.takes().first().copied()).unwrap(),
+                Step::ContinueOrStop => game.decide(GameAction::Stop).unwrap(),
+                Step::Finished(result) => break result,
+                _ => {}
+            }
+        };
+
+        let MatchResult::Won(winner) = result else {
+            panic!("Won by a team without teams");
+        };
+        assert!(game.state().scores()[winner as usize] > 300);
+        assert_eq!(game.result(), Some(result));
+    }
+
+    #[test]
+    fn teams_alternate_turns() {
+        let mut game = Match::with_seed(3);
+        for _ in 0..6 {
+            game.add_player().unwrap();
+        }
+        game.set_limit(1000);
+        game.set_teams(3).unwrap();
+        assert!(matches!(game.add_player(), Err(KostkaError::Teams(_))));
+        assert_eq!(game.state().players_count(), 6);
+
+        let mut last_team = None;
+        let result = loop {
+            match game.step().unwrap() {
+                Step::TurnStarted(player) => {
+                    let team = game.state().team_of(player);
+                    if let Some(last) = last_team {
+                        assert_eq!(team, (last + 1) % 3);
+                    }
+                    last_team = Some(team);
+                }
+                Step::PickTake(hand) => game.take(hand.takes().first().copied()).unwrap(),
+                Step::ContinueOrStop => game.decide(GameAction::Stop).unwrap(),
+                Step::Finished(result) => break result,
+                _ => {}
+            }
+        };
+
+        let MatchResult::TeamWon(team) = result else {
+            panic!("Won by a player in teams");
+        };
+        assert!(game.state().team_scores()[team as usize] > 1000);
+    }
+
+    #[test]
+    fn hot_dice_add_the_bonus() {
+        let mut game = two_players();
+        let rules = Ruleset {
+            dices: 1,
+            hot_dice_bonus: 1000,
+            ..Ruleset::default()
+        };
+        game.set_rules(rules).unwrap();
+
+        // a single dice can only be cleared, the turn goes on until a bust
+        let mut streak = 0;
+        while streak < 2 {
+            match game.step().unwrap() {
+                Step::TurnStarted(_) => assert_eq!(game.state().hot_dices(), 0),
+                Step::PickTake(hand) => {
+                    let before = game.state().turn_score();
+                    let take = hand.takes().first().copied();
+                    game.take(take).unwrap();
+                    if let Some(take) = take {
+                        assert_eq!(game.state().turn_score(), before + take.value + 1000);
+                    }
+                }
+                Step::HotDice(n) => {
+                    assert_eq!(n, streak + 1);
+                    assert_eq!(game.state().hot_dices(), n);
+                    streak = n;
+                }
+                Step::TurnEnded(_, result) => {
+                    assert_eq!(result, TurnResult::Nothing);
+                    streak = 0;
+                }
+                step => panic!("Unexpected {:?}", step),
+            }
+        }
+    }
+
+    #[test]
+    fn asks_at_the_stop_points() {
+        let mut game = two_players();
+        let points = StopPoints::new(&[0, 2]).unwrap();
+        game.set_rules(Ruleset {
+            stop_points: points,
+            ..Ruleset::default()
+        })
+        .unwrap();
+
+        // dices left by the last take
+        let mut left = None;
+        let mut asked = 0;
+        for _ in 0..1000 {
+            match game.step().unwrap() {
+                Step::PickTake(hand) => {
+                    assert!(left.is_none_or(|n| !points.contains(n)));
+                    let take = hand.takes().first().copied();
+                    left = take.map(|take| game.state().dices_available() - take.dices_count());
+                    game.take(take).unwrap();
+                }
+                Step::ContinueOrStop => {
+                    assert!(left.is_some_and(|n| points.contains(n)));
+                    asked += 1;
+                    let action = match game.state().turn_score() {
+                        0..500 => GameAction::Continue,
+                        _ => GameAction::Stop,
+                    };
+                    game.decide(action).unwrap();
+                    left = None;
+                }
+                Step::HotDice(_) => assert_eq!(left, Some(0)),
+                Step::Finished(_) => break,
+                _ => left = None,
+            }
+        }
+        assert!(asked > 0);
+    }
+
+    #[test]
+    fn banks_only_the_minimum() {
+        let mut game = two_players();
+        game.set_rules(Ruleset {
+            min_bank: 1000,
+            ..Ruleset::default()
+        })
+        .unwrap();
+
+        for _ in 0..1000 {
+            match game.step().unwrap() {
+                Step::PickTake(hand) => game.take(hand.takes().first().copied()).unwrap(),
+                Step::ContinueOrStop => {
+                    assert!(game.state().turn_score() >= 1000);
+                    game.decide(GameAction::Stop).unwrap();
+                }
+                Step::TurnEnded(_, TurnResult::Value(value)) => assert!(value >= 1000),
+                Step::Finished(_) => break,
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn piggyback_takes_over_the_turn() {
+        let mut game = two_players();
+        game.set_limit(10000);
+        game.set_rules(Ruleset {
+            piggyback: true,
+            ..Ruleset::default()
+        })
+        .unwrap();
+
+        let mut offers = 0;
+        while offers < 10 {
+            match game.step().unwrap() {
+                Step::PickTake(hand) => game.take(hand.takes().first().copied()).unwrap(),
+                Step::ContinueOrStop => {
+                    assert!(matches!(game.take_over(true), Err(KostkaError::NotAsked)));
+                    game.decide(GameAction::Stop).unwrap();
+                }
+                Step::TakeOver => {
+                    let (score, dices) = game.state().previous_turn().unwrap();
+                    // every other offer is taken
+                    game.take_over(offers % 2 == 0).unwrap();
+                    let expected = match offers % 2 {
+                        0 => (score, dices),
+                        _ => (0, 6),
+                    };
+                    let state = game.state();
+                    assert_eq!((state.turn_score(), state.dices_available()), expected);
+                    offers += 1;
+                }
+                Step::Finished(_) => panic!("Finished before the offers"),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn forfeit_out_of_turn() {
+        let mut game = two_players();
+        game.step().unwrap();
+        game.step().unwrap();
+
+        game.forfeit(1).unwrap();
+        game.take(None).unwrap();
+
+        assert!(matches!(game.step(), Ok(Step::TurnEnded(0, _))));
+        assert!(matches!(
+            game.step(),
+            Ok(Step::Finished(MatchResult::Won(0)))
+        ));
+    }
+}
@@ -0,0 +1,27 @@
+use super::{game::GameState, Decision, GameAction};
+use crate::dice_m::{Hand, TakeOption};
+
+/// Bot picking the most valuable take and ending the turn whenever it may
+#[derive(Debug, Default)]
+pub struct GreedyPlayer;
+
+impl GreedyPlayer {
+    pub fn new() -> Self {
+        GreedyPlayer
+    }
+}
+
+impl Decision for GreedyPlayer {
+    fn pick_take(&mut self, _game_state: &GameState, hand: Hand) -> Option<TakeOption> {
+        // takes are ordered by value, then by fewer dices
+        hand.takes().first().copied()
+    }
+
+    fn continue_or_stop(&mut self, _game_state: &GameState) -> GameAction {
+        GameAction::Stop
+    }
+
+    fn new_round(&mut self) {}
+
+    fn new_dices(&mut self) {}
+}
@@ -3,39 +3,79 @@ use std::io;
 use super::{game::GameState, Decision, GameAction};
 use crate::dice_m::{Hand, TakeOption};
 
-#[derive(Debug)]
+/// Player answering on the terminal, the turn is read from the game state
+#[derive(Debug, Default)]
 pub struct HumanPlayer {
-    round_score: u32,
-    round_dices_used: usize,
+    /// Lists every take instead of the best take of every number of dices
+    all_takes: bool,
+    forfeited: bool,
 }
 
 impl HumanPlayer {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Player choosing from every take of a roll when `all_takes` is set
+    pub fn with_all_takes(all_takes: bool) -> Self {
         HumanPlayer {
-            round_score: 0,
-            round_dices_used: 0,
+            all_takes,
+            ..Self::default()
         }
     }
 
-    pub fn reset(&mut self) {
-        self.round_score = 0;
-        self.round_dices_used = 0;
+    /// Trimmed line of stdin, leaving the game at its end or on I/O errors
+    fn read_line(&mut self) -> Option<String> {
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => {
+                self.forfeited = true;
+                None
+            }
+            Ok(_) => Some(input.trim().to_string()),
+        }
+    }
+}
+
+/// Index of the take numbered `input` among `count` listed from 1
+fn pick_index(input: &str, count: usize) -> Option<usize> {
+    match input.parse::<usize>() {
+        Ok(pick) if (1..=count).contains(&pick) => Some(pick - 1),
+        _ => None,
     }
 }
 
 impl Decision for HumanPlayer {
     fn pick_take(&mut self, game_state: &GameState, hand: Hand) -> Option<TakeOption> {
+        let fresh_dices = game_state.dices_available() == game_state.all_dices();
+        if game_state.hot_dices() > 0 && fresh_dices {
+            println!("Hot dice x{}!", game_state.hot_dices());
+        }
+        print!("score: {} | dices: ", game_state.turn_score());
+        for dice in hand.get_dices() {
+            print!(" {}", dice);
+        }
+        println!();
+
         let takes_to_list = {
             //let hand = self.hand.as_ref().expect("Cannot pick: no hand");
 
-            let mut takes: Vec<&TakeOption> = hand.get_takes().collect();
-            takes.sort_by(|take, other| other.value.cmp(&take.value));
+            // other takes score less while leaving the same dices
+            let takes = match self.all_takes {
+                true => hand.takes().to_vec(),
+                false => hand.best_takes(),
+            };
 
             if takes.is_empty() {
                 return None;
             }
 
-            let must_takes = hand.takes_use_all();
+            let all_dices = hand.dices_used();
+            let must_takes: Vec<TakeOption> = takes
+                .iter()
+                .filter(|take| take.dices_count() == all_dices)
+                .copied()
+                .collect();
 
             match must_takes.len() {
                 0 => takes,
@@ -47,50 +87,71 @@ impl Decision for HumanPlayer {
         };
 
         for (i, take) in takes_to_list.iter().enumerate() {
-            println!("{}) {} - {:?}", i + 1, take.value, take.dices_used);
+            match hand.breakdown(take) {
+                Some(breakdown) => println!("{}) {} - {}", i + 1, take.value, breakdown),
+                None => println!("{}) {} - {:?}", i + 1, take.value, take.dices_used),
+            }
         }
 
-        // user's pick
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).expect("Stdin error");
-        let trimmed_line = input.trim();
-        let pick: i32 = trimmed_line.parse().expect("Not a number");
-
-        let take = takes_to_list.get(pick as usize - 1).map(|&&take| take);
-
-        if let Some(t) = take {
-            self.round_score += t.value();
-            self.round_dices_used += t.dices_count();
+        // user's pick, asked again until it is one of the listed takes
+        let take = loop {
+            let input = self.read_line()?;
+            match pick_index(&input, takes_to_list.len()) {
+                Some(i) => break takes_to_list[i],
+                None => println!("Pick a number from 1 to {}", takes_to_list.len()),
+            }
         };
 
-        take
+        println!("score: {}", game_state.turn_score() + take.value());
+        Some(take)
     }
 
-    fn continue_or_stop(&self, game_state: &GameState) -> GameAction {
-        let dices_left = 6 - self.round_dices_used;
-        match dices_left {
-            1 | 2 => {
-                println!("Do you want to end your turn? (y/n)");
-
-                let mut input = String::new();
-                io::stdin().read_line(&mut input).expect("Stdin error");
-                let trimmed_line = input.trim();
-                if trimmed_line == "y" {
-                    GameAction::Stop
-                } else {
-                    GameAction::Continue
-                }
-            }
-            0 => GameAction::Continue, // todo move to game logic
-            _ => GameAction::Continue,
+    fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction {
+        println!(
+            "Do you want to end your turn with {} points, or roll {} dices? (y/n)",
+            game_state.turn_score(),
+            game_state.dices_available()
+        );
+
+        match self.read_line().as_deref() {
+            Some("y") | None => GameAction::Stop,
+            Some(_) => GameAction::Continue,
         }
     }
 
-    fn new_round(&mut self) {
-        self.reset();
+    fn take_over(&mut self, game_state: &GameState) -> bool {
+        let Some((score, dices)) = game_state.previous_turn() else {
+            return false;
+        };
+        println!(
+            "Do you want to take over {} points and roll {} dices? (y/n)",
+            score, dices
+        );
+
+        self.read_line().as_deref() == Some("y")
+    }
+
+    fn new_round(&mut self) {}
+
+    fn new_dices(&mut self) {}
+
+    fn forfeited(&self) -> bool {
+        self.forfeited
     }
+}
 
-    fn new_dices(&mut self) {
-        self.round_dices_used = 0; // todo zrusit
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_only_listed_takes() {
+        assert_eq!(pick_index("1", 3), Some(0));
+        assert_eq!(pick_index("3", 3), Some(2));
+        assert_eq!(pick_index("0", 3), None);
+        assert_eq!(pick_index("4", 3), None);
+        assert_eq!(pick_index("-1", 3), None);
+        assert_eq!(pick_index("two", 3), None);
+        assert_eq!(pick_index("", 3), None);
     }
 }
@@ -1,8 +1,20 @@
 mod player;
-pub use player::{Decision, GameAction, Player};
+pub use player::{Decision, GameAction, Player, BOT_NAMES, EXTERNAL_PREFIX};
 
 mod human_player;
 pub use human_player::HumanPlayer;
 
+mod greedy_player;
+pub use greedy_player::GreedyPlayer;
+
+mod optimal_player;
+pub use optimal_player::OptimalPlayer;
+
+mod external_bot;
+pub use external_bot::ExternalBot;
+
 mod game;
-pub use game::Game;
+pub use game::{Game, GameState, PlayerInfo, TurnResult};
+
+mod game_match;
+pub use game_match::{Match, MatchResult, Step};
@@ -0,0 +1,359 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::{game::GameState, Decision, GameAction};
+use crate::{
+    dice_m::{
+        distinct_rolls, Hand, RulesCache, Ruleset, StopPoints, TakeOption, TakeTable,
+        CACHED_RULESETS,
+    },
+    KostkaError,
+};
+
+/// Turn scores from which the bot ends the turn as soon as it may
+const SCORE_CAP: u32 = 5000;
+
+/// Chance and takes (dices used, value) of every distinct roll
+type Rolls = Vec<(f64, Vec<(usize, u32)>)>;
+
+/// Expected banked score of a turn, indexed by dices to roll and turn score
+#[derive(Debug)]
+struct TurnValues {
+    values: HashMap<(usize, u32), f64>,
+    bust_chance: Vec<f64>,
+    /// Dices rolled after hot dice
+    dices: usize,
+    hot_dice_bonus: u32,
+    stop_points: StopPoints,
+    min_bank: u32,
+}
+
+impl TurnValues {
+    fn new(rules: &Ruleset) -> Result<Self, KostkaError> {
+        let table = TakeTable::for_rules(rules)?;
+        let rolls: Vec<Rolls> = (1..=rules.dices)
+            .map(|n| {
+                let total = (rules.sides as f64).powi(n as i32);
+                distinct_rolls(n, rules.sides)
+                    .into_iter()
+                    .map(|(dices, count)| {
+                        let hand = Hand::from_dices_in(dices, &table);
+                        // roll values grow with the score, so only the best
+                        // take of every size matters
+                        let takes = hand
+                            .best_takes()
+                            .iter()
+                            .map(|take| (take.dices_count(), take.value()))
+                            .collect();
+                        (count as f64 / total, takes)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let bust_chance = rolls
+            .iter()
+            .map(|n_rolls| {
+                n_rolls
+                    .iter()
+                    .filter(|(_, takes)| takes.is_empty())
+                    .map(|(p, _)| p)
+                    .sum()
+            })
+            .collect();
+
+        let mut table = TurnValues {
+            values: HashMap::new(),
+            bust_chance,
+            dices: rules.dices,
+            hot_dice_bonus: rules.hot_dice_bonus,
+            stop_points: rules.stop_points,
+            min_bank: rules.min_bank,
+        };
+        // every turn score is a multiple of the step, takes are worth at
+        // least a point so filling from the top keeps the recursion shallow
+        let step = score_step(rules);
+        for score in (0..SCORE_CAP).step_by(step as usize).rev() {
+            for dices in 1..=rules.dices {
+                table.compute(&rolls, dices, score);
+            }
+        }
+        Ok(table)
+    }
+
+    fn compute(&mut self, rolls: &[Rolls], dices: usize, score: u32) -> f64 {
+        if let Some(&value) = self.values.get(&(dices, score)) {
+            return value;
+        }
+        if score >= SCORE_CAP {
+            return self.capped(dices, score);
+        }
+
+        let mut value = 0.0;
+        for (chance, takes) in rolls[dices - 1].iter() {
+            let best = takes
+                .iter()
+                .map(|&(used, take_value)| {
+                    let next_score = score + take_value;
+                    let next_dices = dices - used;
+                    let (roll_dices, roll_score) = self.hot_dice(next_dices, next_score);
+                    let roll_on = self.compute(rolls, roll_dices, roll_score);
+                    self.after_take(next_dices, roll_score, roll_on)
+                })
+                .fold(0.0, f64::max);
+            value += chance * best;
+        }
+
+        self.values.insert((dices, score), value);
+        value
+    }
+
+    /// Value after a take, `roll_on` being the value of rolling the dices left
+    /// with the turn score `score`
+    fn after_take(&self, dices_left: usize, score: u32, roll_on: f64) -> f64 {
+        if self.stop_points.contains(dices_left) && score >= self.min_bank {
+            roll_on.max(score as f64)
+        } else {
+            roll_on
+        }
+    }
+
+    /// Dices to roll and turn score after a take leaving `dices_left`, hot
+    /// dices roll all of them again with the bonus
+    fn hot_dice(&self, dices_left: usize, score: u32) -> (usize, u32) {
+        match dices_left {
+            0 => (self.dices, score + self.hot_dice_bonus),
+            n => (n, score),
+        }
+    }
+
+    /// Rolls once more and banks, used above [`SCORE_CAP`]
+    fn capped(&self, dices: usize, score: u32) -> f64 {
+        score as f64 * (1.0 - self.bust_chance[dices - 1])
+    }
+
+    /// Expected banked score when about to roll `dices` dices
+    fn roll_value(&self, dices: usize, score: u32) -> f64 {
+        let (dices, score) = self.hot_dice(dices, score);
+        match self.values.get(&(dices, score)) {
+            Some(&value) => value,
+            None => self.capped(dices, score),
+        }
+    }
+
+    fn take_value(&self, dices_left: usize, score: u32) -> f64 {
+        let (_, roll_score) = self.hot_dice(dices_left, score);
+        self.after_take(dices_left, roll_score, self.roll_value(dices_left, score))
+    }
+}
+
+/// Greatest common divisor of the values of all combinations
+fn score_step(rules: &Ruleset) -> u32 {
+    fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    [
+        rules.five,
+        rules.triple_ones,
+        rules.triple_face,
+        rules.straight,
+        rules.hot_dice_bonus,
+    ]
+    .into_iter()
+    .fold(rules.one, gcd)
+    .max(1)
+}
+
+/// Bot maximising the expected score of its turn
+#[derive(Debug)]
+pub struct OptimalPlayer {
+    values: Arc<TurnValues>,
+}
+
+impl OptimalPlayer {
+    /// Bot playing by the default rules
+    pub fn new() -> Self {
+        Self::with_rules(&Ruleset::default()).expect("Default rules are supported")
+    }
+
+    /// Bot playing by `rules`, its table is shared between the bots of the
+    /// recently used rulesets; errs when `rules` are not supported
+    pub fn with_rules(rules: &Ruleset) -> Result<Self, KostkaError> {
+        static VALUES: RulesCache<TurnValues> = RulesCache::new(CACHED_RULESETS);
+
+        let values = VALUES.get_or_build(rules, TurnValues::new)?;
+        Ok(OptimalPlayer { values })
+    }
+}
+
+impl Default for OptimalPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decision for OptimalPlayer {
+    fn pick_take(&mut self, game_state: &GameState, hand: Hand) -> Option<TakeOption> {
+        let dices = hand.dices_used();
+        let score = game_state.turn_score();
+
+        hand.best_takes()
+            .into_iter()
+            .map(|take| {
+                let value = self
+                    .values
+                    .take_value(dices - take.dices_count(), score + take.value);
+                (value, take)
+            })
+            // dices_used keeps the pick independent of set order
+            .max_by(|(a, a_take), (b, b_take)| {
+                a.total_cmp(b)
+                    .then(b_take.dices_used.cmp(&a_take.dices_used))
+            })
+            .map(|(_, take)| take)
+    }
+
+    fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction {
+        let score = game_state.turn_score();
+        let roll_on = self.values.roll_value(game_state.dices_available(), score);
+        if score as f64 >= roll_on {
+            GameAction::Stop
+        } else {
+            GameAction::Continue
+        }
+    }
+
+    fn take_over(&mut self, game_state: &GameState) -> bool {
+        let Some((score, dices)) = game_state.previous_turn() else {
+            return false;
+        };
+        self.values.roll_value(dices, score) > self.values.roll_value(game_state.all_dices(), 0)
+    }
+
+    fn new_round(&mut self) {}
+
+    fn new_dices(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_m::TurnResult;
+
+    #[test]
+    fn hot_dice_bonus_raises_turn_value() {
+        let values = TurnValues::new(&Ruleset::default()).unwrap();
+        let bonus = TurnValues::new(&Ruleset {
+            hot_dice_bonus: 500,
+            ..Ruleset::default()
+        })
+        .unwrap();
+
+        assert!(bonus.roll_value(6, 0) > values.roll_value(6, 0));
+        assert_eq!(bonus.roll_value(0, 100), bonus.roll_value(6, 600));
+    }
+
+    #[test]
+    fn small_values_fill_the_table() {
+        let values = TurnValues::new(&Ruleset {
+            five: 10,
+            ..Ruleset::default()
+        })
+        .unwrap();
+
+        assert_eq!(values.values.len(), 6 * (SCORE_CAP / 10) as usize);
+        assert!(values.roll_value(6, 0) > 0.0);
+    }
+
+    #[test]
+    fn start_of_turn_beats_single_roll() {
+        let values = TurnValues::new(&Ruleset::default()).unwrap();
+
+        // the best take of 6 dices is worth 366.3 on average
+        assert!(values.roll_value(6, 0) > 366.3);
+    }
+
+    #[test]
+    fn five_dices_are_worth_less() {
+        let five = Ruleset {
+            dices: 5,
+            ..Ruleset::default()
+        };
+        let values = TurnValues::new(&Ruleset::default()).unwrap();
+        let five_values = TurnValues::new(&five).unwrap();
+
+        assert!(five_values.roll_value(5, 0) < values.roll_value(6, 0));
+        assert_eq!(five_values.roll_value(0, 0), five_values.roll_value(5, 0));
+    }
+
+    fn turn(score: u32, dices_left: usize) -> GameState {
+        let mut state = GameState::new();
+        state.add_player();
+        state.set_turn(score, dices_left);
+        state
+    }
+
+    #[test]
+    fn stops_with_big_score() {
+        let mut player = OptimalPlayer::new();
+
+        assert_eq!(player.continue_or_stop(&turn(3000, 1)), GameAction::Stop);
+    }
+
+    #[test]
+    fn continues_with_nothing() {
+        let mut player = OptimalPlayer::new();
+
+        assert_eq!(player.continue_or_stop(&turn(50, 2)), GameAction::Continue);
+        // five dices are worth rolling even though the rules allow a stop
+        assert_eq!(player.continue_or_stop(&turn(100, 5)), GameAction::Continue);
+    }
+
+    #[test]
+    fn banks_only_at_stop_points() {
+        let few = Ruleset {
+            stop_points: StopPoints::new(&[1, 2]).unwrap(),
+            ..Ruleset::default()
+        };
+        let values = TurnValues::new(&few).unwrap();
+        let any = TurnValues::new(&Ruleset::default()).unwrap();
+
+        // more freedom to stop is worth more
+        assert!(any.roll_value(6, 0) > values.roll_value(6, 0));
+        assert!(values.take_value(3, 5000) < 5000.0);
+        assert_eq!(any.take_value(3, 5000), 5000.0);
+    }
+
+    #[test]
+    fn banks_only_the_minimum() {
+        let values = TurnValues::new(&Ruleset {
+            min_bank: 1000,
+            ..Ruleset::default()
+        })
+        .unwrap();
+        let any = TurnValues::new(&Ruleset::default()).unwrap();
+
+        assert!(values.roll_value(6, 0) < any.roll_value(6, 0));
+        assert!(values.take_value(3, 500) < any.take_value(3, 500));
+        assert_eq!(values.take_value(3, 5000), 5000.0);
+    }
+
+    #[test]
+    fn takes_over_rich_turns() {
+        let mut player = OptimalPlayer::new();
+        let left = |score, dices| {
+            let mut state = turn(score, dices);
+            state.add_player();
+            state.update_player_turn(TurnResult::Value(score));
+            state
+        };
+
+        assert!(player.take_over(&left(1000, 5)));
+        assert!(!player.take_over(&left(50, 1)));
+        assert!(!player.take_over(&turn(0, 6)));
+    }
+}
@@ -1,5 +1,6 @@
-use super::{game::GameState, HumanPlayer};
-use crate::dice_m::{Hand, TakeOption};
+use super::{game::GameState, ExternalBot, GreedyPlayer, HumanPlayer, OptimalPlayer};
+use crate::dice_m::{Hand, Ruleset, TakeOption};
+use crate::KostkaError;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum GameAction {
@@ -12,11 +13,50 @@ pub struct Player {
     brain: Box<dyn Decision>,
 }
 
+/// Names of the built-in bots accepted by [`Player::bot`]
+pub const BOT_NAMES: [&str; 2] = ["greedy", "optimal"];
+
+/// Prefix of a command line starting an [`ExternalBot`], e.g. `external:python3 bot.py`
+pub const EXTERNAL_PREFIX: &str = "external:";
+
 impl Player {
-    pub fn human(name: &str) -> Self {
+    pub fn new(name: &str, brain: Box<dyn Decision>) -> Self {
         Player {
             name: name.into(),
-            brain: Box::new(HumanPlayer::new()),
+            brain,
+        }
+    }
+
+    pub fn human(name: &str) -> Self {
+        Self::new(name, Box::new(HumanPlayer::new()))
+    }
+
+    pub fn greedy(name: &str) -> Self {
+        Self::new(name, Box::new(GreedyPlayer::new()))
+    }
+
+    pub fn optimal(name: &str) -> Self {
+        Self::new(name, Box::new(OptimalPlayer::new()))
+    }
+
+    /// Built-in bot by its name in [`BOT_NAMES`] or an external bot
+    /// started from the command line after [`EXTERNAL_PREFIX`]
+    pub fn bot(kind: &str, name: &str) -> Result<Self, KostkaError> {
+        Self::bot_with_rules(kind, name, &Ruleset::default())
+    }
+
+    /// Bot of [`Player::bot`] playing a game by `rules`
+    pub fn bot_with_rules(kind: &str, name: &str, rules: &Ruleset) -> Result<Self, KostkaError> {
+        match kind {
+            "greedy" => Ok(Self::greedy(name)),
+            "optimal" => Ok(Self::new(name, Box::new(OptimalPlayer::with_rules(rules)?))),
+            _ => {
+                let command = kind
+                    .strip_prefix(EXTERNAL_PREFIX)
+                    .ok_or_else(|| KostkaError::UnknownPlayer(kind.into()))?;
+                let bot = ExternalBot::from_command_line(command)?;
+                Ok(Self::new(name, Box::new(bot)))
+            }
         }
     }
 
@@ -28,10 +68,18 @@ impl Player {
         self.brain.pick_take(game_state, hand)
     }
 
-    pub fn continue_or_stop(&self, game_state: &GameState) -> GameAction {
+    pub fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction {
         self.brain.continue_or_stop(game_state)
     }
 
+    pub fn take_over(&mut self, game_state: &GameState) -> bool {
+        self.brain.take_over(game_state)
+    }
+
+    pub fn forfeited(&self) -> bool {
+        self.brain.forfeited()
+    }
+
     pub fn new_round(&mut self) {
         self.brain.new_round();
     }
@@ -48,5 +96,16 @@ pub trait Decision {
 
     fn pick_take(&mut self, game_state: &GameState, hand: Hand) -> Option<TakeOption>;
 
-    fn continue_or_stop(&self, game_state: &GameState) -> GameAction;
+    fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction;
+
+    /// Whether to start the turn from [`GameState::previous_turn`] instead
+    /// of all the dices, asked in the piggyback variant
+    fn take_over(&mut self, _game_state: &GameState) -> bool {
+        false
+    }
+
+    /// Player gave up the game, e.g. after breaking a protocol
+    fn forfeited(&self) -> bool {
+        false
+    }
 }
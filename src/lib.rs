@@ -0,0 +1,27 @@
+pub mod dice_m;
+
+pub mod game_m;
+
+#[cfg(feature = "capi")]
+pub mod capi_m;
+
+#[cfg(feature = "native")]
+pub mod net_m;
+
+#[cfg(feature = "native")]
+pub mod sim_m;
+
+#[cfg(feature = "native")]
+pub mod tui_m;
+
+#[cfg(feature = "native")]
+pub mod web_m;
+
+#[cfg(feature = "wasm")]
+pub mod wasm_m;
+
+#[cfg(feature = "python")]
+pub mod python_m;
+
+mod error;
+pub use error::KostkaError;
@@ -1,22 +1,506 @@
-mod dice_m;
-//use dice_m::*;
+use clap::{Args, Parser, Subcommand};
 
-mod game_m;
-use game_m::*;
+use kostka::dice_m::{analyze_all, DiceStats, Ruleset, StopPoints, MAX_DICES, MAX_SIDES};
+use kostka::game_m::*;
+use kostka::net_m::{join, Server};
+use kostka::sim_m::{simulate, tournament, SimConfig, TournamentConfig};
+use kostka::tui_m::{Screen, TuiPlayer};
+use kostka::KostkaError;
+
+#[derive(Parser)]
+#[command(about = "Simulator of the dice game 'Kostky'")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Dices the game is played with
+#[derive(Args, Clone, Copy)]
+struct RulesArgs {
+    /// Dices rolled at the start of a turn
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(1..=MAX_DICES as i64))]
+    dices: u32,
+    /// Sides of every dice
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(1..=MAX_SIDES as i64))]
+    sides: u32,
+    /// Points added to the turn score every time all the dices score
+    #[arg(long, default_value_t = 0)]
+    hot_dice_bonus: u32,
+    /// Comma separated numbers of dices left with which a player may bank,
+    /// 0 after hot dice [default: any take]
+    #[arg(long, value_parser = parse_stop_points)]
+    stop_points: Option<StopPoints>,
+    /// Smallest turn score a player may bank
+    #[arg(long, default_value_t = 0)]
+    min_bank: u32,
+    /// Lets the next player take over the turn score and dices left by a
+    /// player who banked
+    #[arg(long)]
+    piggyback: bool,
+}
+
+fn parse_stop_points(arg: &str) -> Result<StopPoints, String> {
+    let dices_left = arg
+        .split(',')
+        .map(|n| {
+            n.trim()
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", n))
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    StopPoints::new(&dices_left).map_err(|e| e.to_string())
+}
+
+impl RulesArgs {
+    fn rules(&self) -> Ruleset {
+        Ruleset {
+            dices: self.dices as usize,
+            sides: self.sides,
+            hot_dice_bonus: self.hot_dice_bonus,
+            stop_points: self.stop_points.unwrap_or_default(),
+            min_bank: self.min_bank,
+            piggyback: self.piggyback,
+            ..Ruleset::default()
+        }
+    }
+}
+
+impl Default for RulesArgs {
+    fn default() -> Self {
+        RulesArgs {
+            dices: 6,
+            sides: 6,
+            hot_dice_bonus: 0,
+            stop_points: None,
+            min_bank: 0,
+            piggyback: false,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play a game in the terminal (default)
+    Play {
+        /// Comma separated players in seat order, `human` or a bot
+        #[arg(long, value_delimiter = ',', default_value = "human,human")]
+        players: Vec<String>,
+        #[arg(long, default_value_t = 500)]
+        goal: u32,
+        /// Full-screen terminal UI for the human players
+        #[arg(long)]
+        tui: bool,
+        /// List every take to the human players, not only the best take of
+        /// every number of dices
+        #[arg(long)]
+        all_takes: bool,
+        /// Teams sharing their scores, seat `i` playing for team `i % teams`
+        #[arg(long)]
+        teams: Option<usize>,
+        #[command(flatten)]
+        rules: RulesArgs,
+    },
+    /// Host a game for players joining over the network
+    Serve {
+        #[arg(long, default_value_t = 7777)]
+        port: u16,
+        /// Comma separated players in seat order, `remote` or a bot
+        #[arg(long, value_delimiter = ',', default_value = "remote,remote")]
+        players: Vec<String>,
+        #[arg(long, default_value_t = 500)]
+        goal: u32,
+        #[command(flatten)]
+        rules: RulesArgs,
+    },
+    /// Join a game hosted with `serve`
+    Join {
+        /// Address of the host, e.g. `192.168.1.10:7777`
+        addr: String,
+        /// Who plays the seat, `human` or a built-in bot
+        #[arg(long, default_value = "human")]
+        player: String,
+        /// Full-screen terminal UI for a human player
+        #[arg(long)]
+        tui: bool,
+        /// List every take to a human player, not only the best take of
+        /// every number of dices
+        #[arg(long)]
+        all_takes: bool,
+    },
+    /// Host games for browser clients over HTTP and WebSocket
+    Web {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Bust and score chances for every number of dice
+    Analyze {
+        /// Print comma separated values instead of a table
+        #[arg(long)]
+        csv: bool,
+        /// Print the distribution of the best take instead of the summary
+        #[arg(long)]
+        distribution: bool,
+        #[command(flatten)]
+        rules: RulesArgs,
+    },
+    /// Play headless games between bots and report the results
+    Simulate {
+        #[arg(long, default_value_t = 10000)]
+        games: u64,
+        /// Comma separated bots in seat order
+        #[arg(long, value_delimiter = ',', default_value = "greedy,optimal")]
+        players: Vec<String>,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, default_value_t = 10000)]
+        goal: u32,
+        #[command(flatten)]
+        rules: RulesArgs,
+    },
+    /// Rank bots in a round-robin with Elo ratings
+    Tournament {
+        /// Matches played by every pair of bots
+        #[arg(long, default_value_t = 1000)]
+        matches: u64,
+        /// Comma separated bots, all built-in bots by default
+        #[arg(long, value_delimiter = ',')]
+        players: Vec<String>,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, default_value_t = 10000)]
+        goal: u32,
+        /// Elo K-factor
+        #[arg(long, default_value_t = 16.0)]
+        k_factor: f64,
+        #[command(flatten)]
+        rules: RulesArgs,
+    },
+}
 
 fn main() {
-    println!("Starting game");
+    let cli = Cli::parse();
 
-    let mut game = Game::new();
-    let p1 = Player::human("p1");
-    game.add_player(p1);
+    let command = cli.command.unwrap_or(Command::Play {
+        players: vec!["human".into(), "human".into()],
+        goal: 500,
+        tui: false,
+        all_takes: false,
+        teams: None,
+        rules: RulesArgs::default(),
+    });
 
-    let p2 = Player::human("p2");
-    game.add_player(p2);
+    match command {
+        Command::Play {
+            players,
+            goal,
+            tui,
+            all_takes,
+            teams,
+            rules,
+        } => {
+            if let Err(e) = play(&players, goal, tui, all_takes, teams, &rules.rules()) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Serve {
+            port,
+            players,
+            goal,
+            rules,
+        } => {
+            if let Err(e) = serve(port, &players, goal, &rules.rules()) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Join {
+            addr,
+            player,
+            tui,
+            all_takes,
+        } => {
+            if let Err(e) = join_game(&addr, &player, tui, all_takes) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Web { port } => {
+            if let Err(e) = web(port) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Analyze {
+            csv,
+            distribution,
+            rules,
+        } => {
+            let stats = match analyze_all(&rules.rules()) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            match (csv, distribution) {
+                (false, false) => print_summary(&stats),
+                (true, false) => print_summary_csv(&stats),
+                (false, true) => print_distribution(&stats),
+                (true, true) => print_distribution_csv(&stats),
+            }
+        }
+        Command::Simulate {
+            games,
+            players,
+            seed,
+            goal,
+            rules,
+        } => {
+            check_bots(&players);
+            let config = SimConfig {
+                games,
+                players,
+                seed,
+                goal,
+                rules: rules.rules(),
+            };
+            match simulate(&config) {
+                Ok(report) => print!("{}", report),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Tournament {
+            matches,
+            mut players,
+            seed,
+            goal,
+            k_factor,
+            rules,
+        } => {
+            if players.is_empty() {
+                players = BOT_NAMES.iter().map(|&name| name.into()).collect();
+            }
+            check_bots(&players);
+            let config = TournamentConfig {
+                players,
+                matches,
+                seed,
+                goal,
+                k_factor,
+                rules: rules.rules(),
+            };
+            match tournament(&config) {
+                Ok(report) => print!("{}", report),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+/// Exits when a name is neither a built-in nor an external bot
+fn check_bots(players: &[String]) {
+    let known = |p: &&String| BOT_NAMES.contains(&p.as_str()) || p.starts_with(EXTERNAL_PREFIX);
+    if let Some(unknown) = players.iter().find(|p| !known(p)) {
+        eprintln!(
+            "Unknown bot '{}', expected one of: {} or {}<command>",
+            unknown,
+            BOT_NAMES.join(", "),
+            EXTERNAL_PREFIX
+        );
+        std::process::exit(2);
+    }
+}
+
+fn play(
+    players: &[String],
+    goal: u32,
+    tui: bool,
+    all_takes: bool,
+    teams: Option<usize>,
+    rules: &Ruleset,
+) -> Result<(), KostkaError> {
+    let mut game = Game::new();
+    game.set_rules(*rules)?;
+    // all human players share one screen
+    let screen = match tui {
+        true => Some(Screen::shared()?),
+        false => None,
+    };
+    for (seat, kind) in players.iter().enumerate() {
+        let name = format!("p{}", seat + 1);
+        let player = match (kind.as_str(), &screen) {
+            ("human", Some(screen)) => Player::new(&name, Box::new(TuiPlayer::new(screen.clone()))),
+            ("human", None) => Player::new(&name, Box::new(HumanPlayer::with_all_takes(all_takes))),
+            _ => Player::bot_with_rules(kind, &name, rules)?,
+        };
+        game.add_player(player)?;
+    }
+    if let Some(teams) = teams {
+        game.set_teams(teams)?;
+    }
 
-    game.set_limit(500);
+    game.set_limit(goal);
 
-    game.play();
+    if !tui {
+        println!("Starting game");
+    }
+    let result = game.play();
+    let state = game.state().clone();
+    // restores the terminal before printing the result
+    drop(game);
+    drop(screen);
+    match result? {
+        MatchResult::Won(i) => println!("p{} won", i + 1),
+        MatchResult::TeamWon(team) => {
+            let team = team as usize;
+            let members: Vec<String> = (0..state.players_count())
+                .filter(|&i| state.team_of(i) == team)
+                .map(|i| format!("p{}", i + 1))
+                .collect();
+            println!(
+                "team {} ({}) won with {} points",
+                team + 1,
+                members.join(", "),
+                state.team_scores()[team]
+            );
+        }
+    }
 
     //println!("Game:\n{:?}", game);
+    Ok(())
+}
+
+fn serve(port: u16, players: &[String], goal: u32, rules: &Ruleset) -> Result<(), KostkaError> {
+    let remote = players.iter().filter(|kind| *kind == "remote").count();
+    let server = Server::bind(("0.0.0.0", port), remote, *rules)?;
+
+    let mut game = Game::new();
+    game.set_rules(*rules)?;
+    let mut remote_seats = 0..remote;
+    for (seat, kind) in players.iter().enumerate() {
+        let name = format!("p{}", seat + 1);
+        let player = match kind.as_str() {
+            "remote" => {
+                let remote_seat = remote_seats.next().expect("Seat for every remote player");
+                Player::new(&name, Box::new(server.player(remote_seat)))
+            }
+            _ => Player::bot_with_rules(kind, &name, rules)?,
+        };
+        game.add_player(player)?;
+    }
+    game.set_limit(goal);
+
+    println!(
+        "Listening on {}, waiting for {} players",
+        server.local_addr(),
+        remote
+    );
+    server.wait_for_players();
+    println!("Starting game");
+
+    let result = game.play();
+    let winner = match &result {
+        Ok(MatchResult::Won(i)) => Some(*i as usize),
+        // served games have no teams
+        Ok(MatchResult::TeamWon(_)) | Err(_) => None,
+    };
+    server.finish(winner);
+    match result? {
+        MatchResult::Won(i) => println!("p{} won", i + 1),
+        MatchResult::TeamWon(team) => println!("team {} won", team + 1),
+    }
+    Ok(())
+}
+
+fn join_game(addr: &str, player: &str, tui: bool, all_takes: bool) -> Result<(), KostkaError> {
+    if !["human", "greedy", "optimal"].contains(&player) {
+        return Err(KostkaError::UnknownPlayer(player.into()));
+    }
+    let screen = match (tui, player) {
+        (true, "human") => Some(Screen::shared()?),
+        _ => None,
+    };
+    let winner = join(addr, |rules| -> Result<Box<dyn Decision>, KostkaError> {
+        Ok(match (player, &screen) {
+            (_, Some(screen)) => Box::new(TuiPlayer::new(screen.clone())),
+            ("greedy", None) => Box::new(GreedyPlayer::new()),
+            ("optimal", None) => Box::new(OptimalPlayer::with_rules(rules)?),
+            _ => Box::new(HumanPlayer::with_all_takes(all_takes)),
+        })
+    });
+    // restores the terminal before printing the result
+    drop(screen);
+    match winner? {
+        Some(i) => println!("p{} won", i + 1),
+        None => println!("Left the game"),
+    }
+    Ok(())
+}
+
+fn web(port: u16) -> std::io::Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+        println!("Serving on http://{}", listener.local_addr()?);
+        kostka::web_m::serve(listener).await
+    })
+}
+
+fn print_summary(stats: &[DiceStats]) {
+    println!("dices |  outcomes |   bust | hot dice | expected best");
+    for s in stats {
+        println!(
+            "{:>5} | {:>9} | {:>5.2}% | {:>7.2}% | {:>13.1}",
+            s.dices,
+            s.outcomes,
+            s.bust_chance() * 100.0,
+            s.hot_dice_chance() * 100.0,
+            s.expected_best()
+        );
+    }
+}
+
+fn print_summary_csv(stats: &[DiceStats]) {
+    println!("dices,outcomes,busts,bust_chance,hot_dices,hot_dice_chance,expected_best");
+    for s in stats {
+        println!(
+            "{},{},{},{},{},{},{}",
+            s.dices,
+            s.outcomes,
+            s.busts,
+            s.bust_chance(),
+            s.hot_dices,
+            s.hot_dice_chance(),
+            s.expected_best()
+        );
+    }
+}
+
+fn print_distribution(stats: &[DiceStats]) {
+    for s in stats {
+        println!("{} dices ({} outcomes)", s.dices, s.outcomes);
+        println!("{:>7} | {:>7.3}%", "bust", s.bust_chance() * 100.0);
+        for &value in s.best_takes.keys() {
+            println!("{:>7} | {:>7.3}%", value, s.best_take_chance(value) * 100.0);
+        }
+        println!();
+    }
+}
+
+fn print_distribution_csv(stats: &[DiceStats]) {
+    // best take value 0 is a bust
+    println!("dices,best_take,outcomes,chance");
+    for s in stats {
+        println!("{},0,{},{}", s.dices, s.busts, s.bust_chance());
+        for (&value, &n) in s.best_takes.iter() {
+            println!("{},{},{},{}", s.dices, value, n, s.best_take_chance(value));
+        }
+    }
 }
@@ -0,0 +1,143 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    thread,
+    time::Duration,
+};
+
+use super::protocol::{Action, ClientMessage, Connection, ServerMessage};
+use crate::{
+    dice_m::{Dices, Hand, Ruleset},
+    game_m::{Decision, GameAction},
+    KostkaError,
+};
+
+/// Attempts to rejoin after losing the connection
+const REJOIN_ATTEMPTS: u32 = 30;
+const REJOIN_DELAY: Duration = Duration::from_secs(1);
+
+/// Joined seat of a game hosted by a [`super::Server`]
+struct Client {
+    addrs: Vec<SocketAddr>,
+    connection: Connection,
+    seat: usize,
+    token: u64,
+    rules: Ruleset,
+}
+
+impl Client {
+    fn connect(addrs: &[SocketAddr], hello: &ClientMessage) -> Result<Self, KostkaError> {
+        let mut connection = Connection::new(TcpStream::connect(addrs)?)?;
+        connection.send(hello)?;
+        match connection.receive()? {
+            ServerMessage::Welcome { seat, token, rules } => Ok(Client {
+                addrs: addrs.to_vec(),
+                connection,
+                seat,
+                token,
+                rules,
+            }),
+            ServerMessage::Error { message } => Err(KostkaError::Refused(message)),
+            _ => Err(KostkaError::Refused("no welcome".into())),
+        }
+    }
+
+    /// Connects again to the same seat
+    fn rejoin(&mut self) -> Result<(), KostkaError> {
+        let hello = ClientMessage::Rejoin {
+            seat: self.seat,
+            token: self.token,
+        };
+        let mut attempts = 0;
+        loop {
+            match Self::connect(&self.addrs, &hello) {
+                Ok(client) => {
+                    self.connection = client.connection;
+                    return Ok(());
+                }
+                Err(KostkaError::Io(_)) if attempts < REJOIN_ATTEMPTS => {
+                    attempts += 1;
+                    thread::sleep(REJOIN_DELAY);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Answers the server until the game ends
+    fn play(&mut self, brain: &mut dyn Decision) -> Result<Option<usize>, KostkaError> {
+        loop {
+            let message = match self.connection.receive() {
+                Ok(message) => message,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => return Err(e.into()),
+                Err(_) => {
+                    self.rejoin()?;
+                    continue;
+                }
+            };
+
+            let reply = match message {
+                ServerMessage::NewRound => {
+                    brain.new_round();
+                    None
+                }
+                ServerMessage::NewDices => {
+                    brain.new_dices();
+                    None
+                }
+                ServerMessage::PickTake {
+                    state,
+                    dices,
+                    takes,
+                } => {
+                    let hand =
+                        Hand::from_dices_with_rules(Dices::from(dices.as_slice()), &self.rules)?;
+                    let take = brain.pick_take(&state, hand);
+                    // the server knows the takes by their index only
+                    let take = take.and_then(|take| takes.iter().position(|&t| t == take));
+                    Some(ClientMessage::Pick { take })
+                }
+                ServerMessage::ContinueOrStop { state } => {
+                    let action = match brain.continue_or_stop(&state) {
+                        GameAction::Continue => Action::Continue,
+                        GameAction::Stop => Action::Stop,
+                    };
+                    Some(ClientMessage::Action { action })
+                }
+                ServerMessage::TakeOver { state } => Some(ClientMessage::TakeOver {
+                    accept: brain.take_over(&state),
+                }),
+                ServerMessage::GameOver { winner } => return Ok(winner),
+                ServerMessage::Error { message } => return Err(KostkaError::Refused(message)),
+                ServerMessage::Welcome { .. } => None,
+            };
+
+            if brain.forfeited() {
+                self.connection.send(&ClientMessage::Leave)?;
+                return Ok(None);
+            }
+            if let Some(reply) = reply {
+                // a lost reply is asked for again after rejoining
+                if self.connection.send(&reply).is_err() {
+                    self.rejoin()?;
+                }
+            }
+        }
+    }
+}
+
+/// Plays a seat of the game hosted at `addr`, deciding by the `brain`
+/// made for the rules of the game.
+///
+/// Rejoins the seat when the connection drops. Returns the index of the
+/// winner among all the players, None after leaving the game or when the
+/// game failed on the server.
+pub fn join(
+    addr: impl ToSocketAddrs,
+    brain: impl FnOnce(&Ruleset) -> Result<Box<dyn Decision>, KostkaError>,
+) -> Result<Option<usize>, KostkaError> {
+    let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+    let mut client = Client::connect(&addrs, &ClientMessage::Join)?;
+    let mut brain = brain(&client.rules)?;
+    client.play(brain.as_mut())
+}
@@ -0,0 +1,11 @@
+mod protocol;
+pub use protocol::{Action, ClientMessage, Connection, ServerMessage};
+
+mod remote_player;
+pub use remote_player::RemotePlayer;
+
+mod server;
+pub use server::{Server, RECONNECT_TIMEOUT, REPLY_TIMEOUT};
+
+mod client;
+pub use client::join;
@@ -0,0 +1,119 @@
+//! JSON lines spoken between [`super::Server`] and the joined players.
+//!
+//! A player connects and sends `{"type": "join"}`, or
+//! `{"type": "rejoin", "seat": 1, "token": ...}` after losing the
+//! connection. The server answers `welcome` with the seat, its secret
+//! token and the rules, or `error` and hangs up.
+//!
+//! During the game the server sends `new_round` and `new_dices`, which
+//! expect no reply, and
+//!
+//! - `pick_take` with the state, dices and takes, expecting
+//!   `{"type": "pick", "take": <index into takes>}` or `"take": null` to
+//!   give up the turn
+//! - `continue_or_stop` with the state, expecting
+//!   `{"type": "action", "action": "continue"}` or `"stop"`
+//! - `take_over` with the state in the piggyback variant, expecting
+//!   `{"type": "take_over", "accept": true}` to start from the turn the
+//!   previous player banked
+//!
+//! `{"type": "leave"}` forfeits the game at any time. Every seat gets
+//! `game_over` with the winner at the end.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    dice_m::{Ruleset, TakeOption},
+    game_m::GameState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        seat: usize,
+        /// Proves the seat on rejoining
+        token: u64,
+        rules: Ruleset,
+    },
+    NewRound,
+    NewDices,
+    PickTake {
+        state: GameState,
+        dices: Vec<i32>,
+        takes: Vec<TakeOption>,
+    },
+    ContinueOrStop {
+        state: GameState,
+    },
+    TakeOver {
+        state: GameState,
+    },
+    /// None when the game ended with an error
+    GameOver {
+        winner: Option<usize>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Continue,
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join,
+    Rejoin { seat: usize, token: u64 },
+    Pick { take: Option<usize> },
+    Action { action: Action },
+    TakeOver { accept: bool },
+    Leave,
+}
+
+/// Line-delimited JSON over a TCP stream
+#[derive(Debug)]
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    pub fn stream(&self) -> &TcpStream {
+        &self.writer
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> io::Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Next message, errs with `UnexpectedEof` when the peer hung up and
+    /// with `InvalidData` on malformed messages
+    pub fn receive<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
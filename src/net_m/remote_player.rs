@@ -0,0 +1,229 @@
+use std::{
+    io,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::protocol::{Action, ClientMessage, Connection, ServerMessage};
+use crate::{
+    dice_m::{Hand, TakeOption},
+    game_m::{Decision, GameAction, GameState},
+};
+
+/// Seat of a remote player, its connection is replaced on rejoining
+#[derive(Debug)]
+pub(crate) struct Seat {
+    pub token: u64,
+    slot: Mutex<Slot>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Slot {
+    /// Somebody joined the seat
+    claimed: bool,
+    connection: Option<Connection>,
+}
+
+impl Seat {
+    pub fn new(token: u64) -> Self {
+        Seat {
+            token,
+            slot: Mutex::default(),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Slot> {
+        self.slot.lock().expect("Seat lock poisoned")
+    }
+
+    /// Claims a free seat for a new player, false when taken
+    pub fn claim(&self) -> bool {
+        let mut slot = self.lock();
+        !std::mem::replace(&mut slot.claimed, true)
+    }
+
+    pub fn is_claimed(&self) -> bool {
+        self.lock().claimed
+    }
+
+    /// Seats `connection`, replacing the previous one
+    pub fn connect(&self, connection: Connection) {
+        self.lock().connection = Some(connection);
+        self.changed.notify_all();
+    }
+
+    /// Waits at most `timeout` for a connection and takes it out of the seat
+    fn take_connection(&self, timeout: Duration) -> Option<Connection> {
+        let deadline = Instant::now() + timeout;
+        let mut slot = self.lock();
+        loop {
+            if let Some(connection) = slot.connection.take() {
+                return Some(connection);
+            }
+            let left = deadline.checked_duration_since(Instant::now())?;
+            slot = self
+                .changed
+                .wait_timeout(slot, left)
+                .expect("Seat lock poisoned")
+                .0;
+        }
+    }
+
+    /// Puts back a working connection unless the player rejoined meanwhile
+    fn return_connection(&self, connection: Connection) {
+        let mut slot = self.lock();
+        if slot.connection.is_none() {
+            slot.connection = Some(connection);
+        }
+    }
+
+    /// Sends `message` if the seat is connected, e.g. to announce the winner
+    pub fn notify(&self, message: &ServerMessage) {
+        let mut slot = self.lock();
+        if let Some(connection) = slot.connection.as_mut() {
+            if connection.send(message).is_err() {
+                slot.connection = None;
+            }
+        }
+    }
+}
+
+/// Player sitting at another computer, asked over a [`Connection`].
+///
+/// A player who hangs up has `reconnect_timeout` to rejoin before the
+/// question is asked again; a player who does not rejoin, replies late or
+/// replies nonsense forfeits. Picked takes are indices into the offered
+/// takes, so the server never takes the player's word for a take.
+#[derive(Debug)]
+pub struct RemotePlayer {
+    seat: Arc<Seat>,
+    reply_timeout: Duration,
+    reconnect_timeout: Duration,
+    forfeited: bool,
+}
+
+impl RemotePlayer {
+    pub(crate) fn new(
+        seat: Arc<Seat>,
+        reply_timeout: Duration,
+        reconnect_timeout: Duration,
+    ) -> Self {
+        RemotePlayer {
+            seat,
+            reply_timeout,
+            reconnect_timeout,
+            forfeited: false,
+        }
+    }
+
+    /// Sends `message` and waits for a reply `parse` accepts, asking again
+    /// after the player rejoins
+    fn ask<T>(
+        &mut self,
+        message: &ServerMessage,
+        parse: impl Fn(ClientMessage) -> Option<T>,
+    ) -> Option<T> {
+        while !self.forfeited {
+            let Some(mut connection) = self.seat.take_connection(self.reconnect_timeout) else {
+                self.forfeited = true;
+                break;
+            };
+
+            let reply = connection
+                .stream()
+                .set_read_timeout(Some(self.reply_timeout))
+                .and_then(|_| connection.send(message))
+                .and_then(|_| connection.receive::<ClientMessage>());
+            match reply {
+                Ok(ClientMessage::Leave) => self.forfeited = true,
+                Ok(reply) => {
+                    let value = parse(reply);
+                    self.forfeited = value.is_none();
+                    self.seat.return_connection(connection);
+                    return value;
+                }
+                // too slow to reply or malformed reply
+                Err(e) if is_misbehaving(&e) => self.forfeited = true,
+                // hung up, wait for them to rejoin
+                Err(_) => {}
+            }
+        }
+        None
+    }
+
+    /// Sends a message expecting no reply, the seat keeps the connection
+    /// only if it works
+    fn tell(&mut self, message: &ServerMessage) {
+        if self.forfeited {
+            return;
+        }
+        if let Some(mut connection) = self.seat.take_connection(Duration::ZERO) {
+            if connection.send(message).is_ok() {
+                self.seat.return_connection(connection);
+            }
+        }
+    }
+}
+
+fn is_misbehaving(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::InvalidData
+    )
+}
+
+impl Decision for RemotePlayer {
+    fn pick_take(&mut self, game_state: &GameState, hand: Hand) -> Option<TakeOption> {
+        let takes = hand.takes().to_vec();
+        let message = ServerMessage::PickTake {
+            state: game_state.clone(),
+            dices: hand.get_dices().iter().map(|dice| dice.value).collect(),
+            takes: takes.clone(),
+        };
+        let pick = self.ask(&message, |reply| match reply {
+            ClientMessage::Pick { take: None } => Some(None),
+            ClientMessage::Pick { take: Some(i) } => takes.get(i).map(|&take| Some(take)),
+            _ => None,
+        });
+        pick.flatten()
+    }
+
+    fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction {
+        let message = ServerMessage::ContinueOrStop {
+            state: game_state.clone(),
+        };
+        let action = self.ask(&message, |reply| match reply {
+            ClientMessage::Action { action } => Some(action),
+            _ => None,
+        });
+        match action {
+            Some(Action::Continue) => GameAction::Continue,
+            _ => GameAction::Stop,
+        }
+    }
+
+    fn take_over(&mut self, game_state: &GameState) -> bool {
+        let message = ServerMessage::TakeOver {
+            state: game_state.clone(),
+        };
+        let accept = self.ask(&message, |reply| match reply {
+            ClientMessage::TakeOver { accept } => Some(accept),
+            _ => None,
+        });
+        accept.unwrap_or(false)
+    }
+
+    fn new_round(&mut self) {
+        self.tell(&ServerMessage::NewRound);
+    }
+
+    fn new_dices(&mut self) {
+        self.tell(&ServerMessage::NewDices);
+    }
+
+    fn forfeited(&self) -> bool {
+        self.forfeited
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use super::{
+    protocol::{ClientMessage, Connection, ServerMessage},
+    remote_player::{RemotePlayer, Seat},
+};
+use crate::dice_m::Ruleset;
+
+/// Time a remote player has for every reply
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Time a remote player who hung up has to rejoin
+pub const RECONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Time a new connection has to say which seat it wants
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Host of one game, seating the players who connect over TCP
+pub struct Server {
+    addr: SocketAddr,
+    seats: Vec<Arc<Seat>>,
+    reply_timeout: Duration,
+    reconnect_timeout: Duration,
+}
+
+impl Server {
+    /// Listens on `addr` for the players of `seats` remote seats, the game
+    /// is played by `rules`
+    pub fn bind(addr: impl ToSocketAddrs, seats: usize, rules: Ruleset) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let seats: Vec<Arc<Seat>> = (0..seats)
+            .map(|_| Arc::new(Seat::new(rand::random())))
+            .collect();
+
+        let accepted = seats.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // a stuck handshake must not keep others from joining
+                let seats = accepted.clone();
+                thread::spawn(move || {
+                    let _ = seat_connection(stream, &seats, &rules);
+                });
+            }
+        });
+
+        Ok(Server {
+            addr,
+            seats,
+            reply_timeout: REPLY_TIMEOUT,
+            reconnect_timeout: RECONNECT_TIMEOUT,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn set_timeouts(&mut self, reply: Duration, reconnect: Duration) {
+        self.reply_timeout = reply;
+        self.reconnect_timeout = reconnect;
+    }
+
+    /// Number of remote seats
+    pub fn seats(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// Blocks until every seat was joined
+    pub fn wait_for_players(&self) {
+        while !self.seats.iter().all(|seat| seat.is_claimed()) {
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    /// Player of the `seat`-th remote seat
+    pub fn player(&self, seat: usize) -> RemotePlayer {
+        RemotePlayer::new(
+            self.seats[seat].clone(),
+            self.reply_timeout,
+            self.reconnect_timeout,
+        )
+    }
+
+    /// Tells every connected player who won, None when the game failed
+    pub fn finish(&self, winner: Option<usize>) {
+        for seat in &self.seats {
+            seat.notify(&ServerMessage::GameOver { winner });
+        }
+    }
+}
+
+/// Seats a new connection by its join or rejoin message
+fn seat_connection(stream: TcpStream, seats: &[Arc<Seat>], rules: &Ruleset) -> io::Result<()> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut connection = Connection::new(stream)?;
+
+    let seat = match connection.receive()? {
+        ClientMessage::Join => seats.iter().position(|seat| seat.claim()),
+        ClientMessage::Rejoin { seat, token } => seats
+            .get(seat)
+            .filter(|s| s.token == token && s.is_claimed())
+            .map(|_| seat),
+        _ => None,
+    };
+    let Some(seat) = seat else {
+        return connection.send(&ServerMessage::Error {
+            message: "No such seat free".into(),
+        });
+    };
+
+    connection.send(&ServerMessage::Welcome {
+        seat,
+        token: seats[seat].token,
+        rules: *rules,
+    })?;
+    seats[seat].connect(connection);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dice_m::{Dices, Hand},
+        game_m::{Decision, Game, GameState, GreedyPlayer, MatchResult, Player},
+        net_m::join,
+    };
+
+    fn server(seats: usize) -> Server {
+        let mut server = Server::bind("127.0.0.1:0", seats, Ruleset::default()).unwrap();
+        server.set_timeouts(Duration::from_secs(2), Duration::from_secs(2));
+        server
+    }
+
+    /// Raw connection to `server` after the handshake `hello`
+    fn connect(server: &Server, hello: &ClientMessage) -> (Connection, ServerMessage) {
+        let mut connection =
+            Connection::new(TcpStream::connect(server.local_addr()).unwrap()).unwrap();
+        connection.send(hello).unwrap();
+        let welcome = connection.receive().unwrap();
+        (connection, welcome)
+    }
+
+    fn hand() -> Hand {
+        Hand::from_dices(Dices::from([1, 1, 3, 4, 4, 5])).unwrap()
+    }
+
+    #[test]
+    fn plays_remote_game() {
+        let server = server(2);
+        let addr = server.local_addr();
+        let clients: Vec<_> = (0..2)
+            .map(|_| {
+                thread::spawn(move || {
+                    join(addr, |_: &Ruleset| -> Result<Box<dyn Decision>, _> {
+                        Ok(Box::new(GreedyPlayer::new()))
+                    })
+                })
+            })
+            .collect();
+
+        let mut game = Game::with_seed(3);
+        game.add_player(Player::new("p1", Box::new(server.player(0))))
+            .unwrap();
+        game.add_player(Player::new("p2", Box::new(server.player(1))))
+            .unwrap();
+        game.set_limit(1000);
+        server.wait_for_players();
+        let Ok(MatchResult::Won(winner)) = game.play() else {
+            panic!("No winner");
+        };
+        server.finish(Some(winner as usize));
+
+        for client in clients {
+            assert_eq!(client.join().unwrap().unwrap(), Some(winner as usize));
+        }
+        assert!(game.scores()[winner as usize] > 1000);
+    }
+
+    #[test]
+    fn asks_again_after_rejoin() {
+        let server = server(1);
+        let mut player = server.player(0);
+
+        let (mut connection, welcome) = connect(&server, &ClientMessage::Join);
+        let ServerMessage::Welcome { seat, token, .. } = welcome else {
+            panic!("Not welcomed: {:?}", welcome);
+        };
+        let addr = server.local_addr();
+        let client = thread::spawn(move || {
+            // hang up on the first question
+            let _: ServerMessage = connection.receive().unwrap();
+            drop(connection);
+
+            let mut connection = Connection::new(TcpStream::connect(addr).unwrap()).unwrap();
+            connection
+                .send(&ClientMessage::Rejoin { seat, token })
+                .unwrap();
+            let _: ServerMessage = connection.receive().unwrap();
+            let question: ServerMessage = connection.receive().unwrap();
+            connection
+                .send(&ClientMessage::Pick { take: Some(0) })
+                .unwrap();
+            question
+        });
+
+        let take = player.pick_take(&GameState::new(), hand());
+
+        assert_eq!(take, Some(hand().takes()[0]));
+        assert!(!player.forfeited());
+        assert!(matches!(
+            client.join().unwrap(),
+            ServerMessage::PickTake { .. }
+        ));
+    }
+
+    #[test]
+    fn rejects_wrong_token() {
+        let server = server(1);
+        let (_connection, _) = connect(&server, &ClientMessage::Join);
+
+        let (_, reply) = connect(
+            &server,
+            &ClientMessage::Rejoin {
+                seat: 0,
+                token: server.seats[0].token ^ 1,
+            },
+        );
+        assert!(matches!(reply, ServerMessage::Error { .. }));
+        let (_, reply) = connect(&server, &ClientMessage::Join);
+        assert!(matches!(reply, ServerMessage::Error { .. }));
+    }
+
+    #[test]
+    fn take_out_of_range_forfeits() {
+        let server = server(1);
+        let mut player = server.player(0);
+        let (mut connection, _) = connect(&server, &ClientMessage::Join);
+        let client = thread::spawn(move || {
+            let _: ServerMessage = connection.receive().unwrap();
+            connection
+                .send(&ClientMessage::Pick { take: Some(99) })
+                .unwrap();
+        });
+
+        assert_eq!(player.pick_take(&GameState::new(), hand()), None);
+        assert!(player.forfeited());
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn missing_player_forfeits() {
+        let mut server = server(1);
+        server.set_timeouts(Duration::from_millis(100), Duration::from_millis(100));
+        let mut player = server.player(0);
+
+        assert_eq!(player.pick_take(&GameState::new(), hand()), None);
+        assert!(player.forfeited());
+    }
+}
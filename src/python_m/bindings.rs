@@ -0,0 +1,808 @@
+use std::{cell::RefCell, rc::Rc};
+
+use pyo3::{
+    exceptions::{PyOSError, PyTypeError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
+
+use super::py_player::PyPlayer;
+use crate::{
+    dice_m::{analyze_all, Dices, Hand, Ruleset, StopPoints, TakeOption},
+    game_m::{
+        Decision, Game, GameAction, GameState, GreedyPlayer, Match, MatchResult, OptimalPlayer,
+        Player, Step, TurnResult,
+    },
+    KostkaError,
+};
+
+impl From<KostkaError> for PyErr {
+    fn from(e: KostkaError) -> Self {
+        match e {
+            KostkaError::Io(e) => PyOSError::new_err(e.to_string()),
+            e => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+fn rules_or_default(rules: Option<PyRef<PyRuleset>>) -> Ruleset {
+    rules.map_or_else(Ruleset::default, |rules| rules.rules)
+}
+
+/// Dices and point values, e.g. `Ruleset(dices=5, straight=1500)`
+#[pyclass(name = "Ruleset", module = "kostka", frozen, eq)]
+#[derive(PartialEq)]
+pub struct PyRuleset {
+    rules: Ruleset,
+}
+
+#[pymethods]
+impl PyRuleset {
+    /// Default rules with the given values replaced
+    #[new]
+    #[pyo3(signature = (**values))]
+    fn new(values: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut rules = Ruleset::default();
+        for (name, value) in values.into_iter().flatten() {
+            let name: String = name.extract()?;
+            match name.as_str() {
+                "dices" => rules.dices = value.extract()?,
+                "sides" => rules.sides = value.extract()?,
+                "one" => rules.one = value.extract()?,
+                "five" => rules.five = value.extract()?,
+                "straight" => rules.straight = value.extract()?,
+                "triple_ones" => rules.triple_ones = value.extract()?,
+                "triple_face" => rules.triple_face = value.extract()?,
+                "hot_dice_bonus" => rules.hot_dice_bonus = value.extract()?,
+                "stop_points" => {
+                    rules.stop_points = StopPoints::new(&value.extract::<Vec<usize>>()?)?
+                }
+                "min_bank" => rules.min_bank = value.extract()?,
+                "piggyback" => rules.piggyback = value.extract()?,
+                _ => return Err(PyTypeError::new_err(format!("no rule '{}'", name))),
+            }
+        }
+        rules.validate()?;
+        Ok(PyRuleset { rules })
+    }
+
+    #[getter]
+    fn dices(&self) -> usize {
+        self.rules.dices
+    }
+
+    #[getter]
+    fn sides(&self) -> u32 {
+        self.rules.sides
+    }
+
+    #[getter]
+    fn one(&self) -> u32 {
+        self.rules.one
+    }
+
+    #[getter]
+    fn five(&self) -> u32 {
+        self.rules.five
+    }
+
+    #[getter]
+    fn straight(&self) -> u32 {
+        self.rules.straight
+    }
+
+    #[getter]
+    fn triple_ones(&self) -> u32 {
+        self.rules.triple_ones
+    }
+
+    #[getter]
+    fn triple_face(&self) -> u32 {
+        self.rules.triple_face
+    }
+
+    #[getter]
+    fn hot_dice_bonus(&self) -> u32 {
+        self.rules.hot_dice_bonus
+    }
+
+    /// Dices left with which a player may bank, 0 after hot dice
+    #[getter]
+    fn stop_points(&self) -> Vec<usize> {
+        self.rules.stop_points.into()
+    }
+
+    #[getter]
+    fn min_bank(&self) -> u32 {
+        self.rules.min_bank
+    }
+
+    #[getter]
+    fn piggyback(&self) -> bool {
+        self.rules.piggyback
+    }
+
+    fn __repr__(&self) -> String {
+        let r = &self.rules;
+        format!(
+            "Ruleset(dices={}, sides={}, one={}, five={}, straight={}, triple_ones={}, triple_face={}, hot_dice_bonus={}, stop_points={:?}, min_bank={}, piggyback={})",
+            r.dices,
+            r.sides,
+            r.one,
+            r.five,
+            r.straight,
+            r.triple_ones,
+            r.triple_face,
+            r.hot_dice_bonus,
+            Vec::from(r.stop_points),
+            r.min_bank,
+            if r.piggyback { "True" } else { "False" }
+        )
+    }
+}
+
+/// Take of a hand, `dices_used` counting the dices of every face, one
+/// count per side of the dice
+#[pyclass(name = "Take", module = "kostka", frozen, eq)]
+#[derive(PartialEq)]
+pub struct PyTake {
+    pub(crate) take: TakeOption,
+    /// e.g. `one 1 (100) + three 5s (500)`
+    breakdown: String,
+}
+
+#[pymethods]
+impl PyTake {
+    #[getter]
+    fn dices_used(&self) -> Vec<u32> {
+        self.take.dices_used.to_vec()
+    }
+
+    #[getter]
+    fn value(&self) -> u32 {
+        self.take.value
+    }
+
+    #[getter]
+    fn dices_count(&self) -> usize {
+        self.take.dices_count()
+    }
+
+    #[getter]
+    fn breakdown(&self) -> &str {
+        &self.breakdown
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Take({}, {:?})", self.take.value, self.breakdown)
+    }
+}
+
+fn take_views(hand: &Hand) -> Vec<PyTake> {
+    hand.takes()
+        .iter()
+        .map(|&take| PyTake {
+            take,
+            breakdown: hand
+                .breakdown(&take)
+                .map(|breakdown| breakdown.to_string())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn dice_values(hand: &Hand) -> Vec<i32> {
+    hand.get_dices().iter().map(|dice| dice.value).collect()
+}
+
+/// Roll and its takes, best first
+#[pyclass(name = "Hand", module = "kostka", frozen)]
+pub struct PyHand {
+    hand: Hand,
+}
+
+impl From<Hand> for PyHand {
+    fn from(hand: Hand) -> Self {
+        PyHand { hand }
+    }
+}
+
+#[pymethods]
+impl PyHand {
+    /// Hand of known dices, e.g. `Hand.from_dices([1, 5, 5])`
+    #[staticmethod]
+    #[pyo3(signature = (dices, rules=None))]
+    fn from_dices(dices: Vec<i32>, rules: Option<PyRef<PyRuleset>>) -> PyResult<Self> {
+        let rules = rules_or_default(rules);
+        let hand = Hand::from_dices_with_rules(Dices::from(dices.as_slice()), &rules)?;
+        Ok(PyHand { hand })
+    }
+
+    fn dices(&self) -> Vec<i32> {
+        dice_values(&self.hand)
+    }
+
+    fn takes(&self) -> Vec<PyTake> {
+        take_views(&self.hand)
+    }
+
+    /// Value of the best take, 0 for a bust
+    fn best_value(&self) -> u32 {
+        self.hand.takes().first().map_or(0, |take| take.value)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Hand({:?})", dice_values(&self.hand))
+    }
+}
+
+/// Scores, goal and turn of a game as seen by the player on turn
+#[pyclass(name = "GameState", module = "kostka", frozen)]
+pub struct PyGameState {
+    state: GameState,
+}
+
+impl From<GameState> for PyGameState {
+    fn from(state: GameState) -> Self {
+        PyGameState { state }
+    }
+}
+
+#[pymethods]
+impl PyGameState {
+    /// Index of the player on turn
+    #[getter]
+    fn player(&self) -> usize {
+        self.state.my_index()
+    }
+
+    #[getter]
+    fn scores(&self) -> Vec<u32> {
+        self.state.scores().to_vec()
+    }
+
+    #[getter]
+    fn my_score(&self) -> u32 {
+        self.state.my_score()
+    }
+
+    #[getter]
+    fn goal(&self) -> u32 {
+        self.state.goal()
+    }
+
+    #[getter]
+    fn round(&self) -> u16 {
+        self.state.round()
+    }
+
+    /// Points of the turn not banked yet
+    #[getter]
+    fn turn_score(&self) -> u32 {
+        self.state.turn_score()
+    }
+
+    #[getter]
+    fn dices_available(&self) -> usize {
+        self.state.dices_available()
+    }
+
+    #[getter]
+    fn all_dices(&self) -> usize {
+        self.state.all_dices()
+    }
+
+    /// Times all the dices scored in the current turn
+    #[getter]
+    fn hot_dices(&self) -> u32 {
+        self.state.hot_dices()
+    }
+
+    /// Banked points of every team, of every player without teams
+    #[getter]
+    fn team_scores(&self) -> Vec<u32> {
+        self.state.team_scores()
+    }
+
+    /// `(turn_score, dices)` the previous player banked with, if they did
+    #[getter]
+    fn previous_turn(&self) -> Option<(u32, usize)> {
+        self.state.previous_turn()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "GameState(player={}, scores={:?}, turn_score={}, dices_available={})",
+            self.state.my_index(),
+            self.state.scores(),
+            self.state.turn_score(),
+            self.state.dices_available()
+        )
+    }
+}
+
+/// Answer to `continue_or_stop`
+#[pyclass(name = "Action", module = "kostka", eq, eq_int, from_py_object)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum PyAction {
+    Continue,
+    Stop,
+}
+
+impl From<PyAction> for GameAction {
+    fn from(action: PyAction) -> Self {
+        match action {
+            PyAction::Continue => GameAction::Continue,
+            PyAction::Stop => GameAction::Stop,
+        }
+    }
+}
+
+impl From<GameAction> for PyAction {
+    fn from(action: GameAction) -> Self {
+        match action {
+            GameAction::Continue => PyAction::Continue,
+            GameAction::Stop => PyAction::Stop,
+        }
+    }
+}
+
+/// Built-in bot, e.g. `Bot("optimal")`, to play against or to build on
+#[pyclass(name = "Bot", module = "kostka")]
+pub struct PyBot {
+    brain: Box<dyn Decision + Send + Sync>,
+}
+
+#[pymethods]
+impl PyBot {
+    #[new]
+    #[pyo3(signature = (kind, rules=None))]
+    fn new(kind: &str, rules: Option<PyRef<PyRuleset>>) -> PyResult<Self> {
+        let brain: Box<dyn Decision + Send + Sync> = match kind {
+            "greedy" => Box::new(GreedyPlayer::new()),
+            "optimal" => Box::new(OptimalPlayer::with_rules(&rules_or_default(rules))?),
+            _ => return Err(KostkaError::UnknownPlayer(kind.into()).into()),
+        };
+        Ok(PyBot { brain })
+    }
+
+    fn new_round(&mut self) {
+        self.brain.new_round();
+    }
+
+    fn new_dices(&mut self) {
+        self.brain.new_dices();
+    }
+
+    fn pick_take(&mut self, state: PyRef<PyGameState>, hand: PyRef<PyHand>) -> Option<PyTake> {
+        let take = self.brain.pick_take(&state.state, hand.hand.clone())?;
+        take_views(&hand.hand)
+            .into_iter()
+            .find(|view| view.take == take)
+    }
+
+    fn continue_or_stop(&mut self, state: PyRef<PyGameState>) -> PyAction {
+        self.brain.continue_or_stop(&state.state).into()
+    }
+
+    fn take_over(&mut self, state: PyRef<PyGameState>) -> bool {
+        self.brain.take_over(&state.state)
+    }
+}
+
+fn turn_result(result: TurnResult) -> (&'static str, u32) {
+    match result {
+        TurnResult::Nothing => ("nothing", 0),
+        TurnResult::Forfeit => ("forfeit", 0),
+        TurnResult::Value(value) => ("value", value),
+    }
+}
+
+/// Game engine stepped from Python, the caller asks its players
+#[pyclass(name = "Match", module = "kostka")]
+pub struct PyMatch {
+    game: Match,
+}
+
+#[pymethods]
+impl PyMatch {
+    /// Match of `players` to `goal` points, rolling from `seed` when given,
+    /// player `i` playing for team `i % teams` when given
+    #[new]
+    #[pyo3(signature = (players, goal=500, seed=None, rules=None, teams=None))]
+    fn new(
+        players: usize,
+        goal: u32,
+        seed: Option<u64>,
+        rules: Option<PyRef<PyRuleset>>,
+        teams: Option<usize>,
+    ) -> PyResult<Self> {
+        let mut game = seed.map_or_else(Match::new, Match::with_seed);
+        game.set_rules(rules_or_default(rules))?;
+        game.set_limit(goal);
+        for _ in 0..players {
+            game.add_player()?;
+        }
+        if let Some(teams) = teams {
+            game.set_teams(teams)?;
+        }
+        Ok(PyMatch { game })
+    }
+
+    /// Next event or the unanswered question as a dict tagged by `type`,
+    /// see `Match::step` of the crate
+    fn step<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let player = self.game.state().my_index();
+        let event = PyDict::new(py);
+        match self.game.step()? {
+            Step::TurnStarted(player) => {
+                event.set_item("type", "turn_started")?;
+                event.set_item("player", player)?;
+            }
+            Step::PickTake(hand) => {
+                event.set_item("type", "pick_take")?;
+                event.set_item("player", player)?;
+                event.set_item("dices", dice_values(&hand))?;
+                event.set_item("takes", take_views(&hand))?;
+            }
+            Step::TakeOver => {
+                let (turn_score, dices_available) =
+                    self.game.state().previous_turn().unwrap_or_default();
+                event.set_item("type", "take_over")?;
+                event.set_item("player", player)?;
+                event.set_item("turn_score", turn_score)?;
+                event.set_item("dices_available", dices_available)?;
+            }
+            Step::ContinueOrStop => {
+                event.set_item("type", "continue_or_stop")?;
+                event.set_item("player", player)?;
+                event.set_item("turn_score", self.game.state().turn_score())?;
+                event.set_item("dices_available", self.game.state().dices_available())?;
+            }
+            Step::HotDice(streak) => {
+                event.set_item("type", "hot_dice")?;
+                event.set_item("player", player)?;
+                event.set_item("streak", streak)?;
+            }
+            Step::TurnEnded(player, result) => {
+                let (result, value) = turn_result(result);
+                event.set_item("type", "turn_ended")?;
+                event.set_item("player", player)?;
+                event.set_item("result", result)?;
+                event.set_item("value", value)?;
+            }
+            Step::Finished(MatchResult::Won(winner)) => {
+                event.set_item("type", "finished")?;
+                event.set_item("winner", winner)?;
+            }
+            Step::Finished(MatchResult::TeamWon(team)) => {
+                event.set_item("type", "finished")?;
+                event.set_item("team", team)?;
+            }
+        }
+        Ok(event)
+    }
+
+    /// Picks the take at `index` of the asked `pick_take`, None gives up
+    /// the turn
+    #[pyo3(signature = (index=None))]
+    fn take(&mut self, index: Option<usize>) -> PyResult<()> {
+        let Some(hand) = self.game.asked_hand() else {
+            return Err(KostkaError::NotAsked.into());
+        };
+        let take = match index {
+            Some(i) => Some(
+                *hand
+                    .takes()
+                    .get(i)
+                    .ok_or_else(|| PyValueError::new_err("no take at the index"))?,
+            ),
+            None => None,
+        };
+        Ok(self.game.take(take)?)
+    }
+
+    /// Answers `continue_or_stop`
+    fn decide(&mut self, action: PyAction) -> PyResult<()> {
+        Ok(self.game.decide(action.into())?)
+    }
+
+    /// Answers `take_over`, True starts from the offered turn
+    fn take_over(&mut self, accept: bool) -> PyResult<()> {
+        Ok(self.game.take_over(accept)?)
+    }
+
+    fn forfeit(&mut self, player: usize) -> PyResult<()> {
+        Ok(self.game.forfeit(player)?)
+    }
+
+    #[getter]
+    fn state(&self) -> PyGameState {
+        self.game.state().clone().into()
+    }
+
+    /// Winner once the match is over, None when playing in teams
+    #[getter]
+    fn winner(&self) -> Option<u16> {
+        match self.game.result()? {
+            MatchResult::Won(winner) => Some(winner),
+            MatchResult::TeamWon(_) => None,
+        }
+    }
+
+    /// Winning team once the match is over in teams
+    #[getter]
+    fn winning_team(&self) -> Option<u16> {
+        match self.game.result()? {
+            MatchResult::TeamWon(team) => Some(team),
+            MatchResult::Won(_) => None,
+        }
+    }
+}
+
+/// Plays a game to the end and returns `{"winner", "scores", "rounds"}`,
+/// `{"team", "scores", "team_scores", "rounds"}` when `teams` is given.
+///
+/// Players are bot names, e.g. `"optimal"` or `"external:python3 bot.py"`,
+/// or objects with `pick_take(state, hand)` returning a take of the hand or
+/// None, and `continue_or_stop(state)` returning an `Action`, optionally
+/// `take_over(state)` returning a bool. An exception of a player is raised
+/// once the game is over.
+#[pyfunction]
+#[pyo3(signature = (players, goal=500, seed=None, rules=None, teams=None))]
+fn play_game<'py>(
+    py: Python<'py>,
+    players: Vec<Bound<'py, PyAny>>,
+    goal: u32,
+    seed: Option<u64>,
+    rules: Option<PyRef<PyRuleset>>,
+    teams: Option<usize>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let rules = rules_or_default(rules);
+    let mut game = seed.map_or_else(Game::new, Game::with_seed);
+    game.set_rules(rules)?;
+    game.set_limit(goal);
+
+    let failure = Rc::new(RefCell::new(None));
+    for (i, player) in players.into_iter().enumerate() {
+        let name = format!("player {}", i + 1);
+        let player = match player.extract::<&str>() {
+            Ok(kind) => Player::bot_with_rules(kind, &name, &rules)?,
+            Err(_) => Player::new(
+                &name,
+                Box::new(PyPlayer::new(player.unbind(), failure.clone())),
+            ),
+        };
+        game.add_player(player)?;
+    }
+    if let Some(teams) = teams {
+        game.set_teams(teams)?;
+    }
+
+    let result = game.play();
+    if let Some(e) = failure.take() {
+        return Err(e);
+    }
+    let summary = PyDict::new(py);
+    match result? {
+        MatchResult::Won(winner) => summary.set_item("winner", winner)?,
+        MatchResult::TeamWon(team) => summary.set_item("team", team)?,
+    }
+    summary.set_item("scores", game.scores())?;
+    if teams.is_some() {
+        summary.set_item("team_scores", game.state().team_scores())?;
+    }
+    summary.set_item("rounds", game.rounds())?;
+    Ok(summary)
+}
+
+/// Statistics of a single roll of 1 to `rules.dices` dices, one dict per
+/// dice count, e.g. `pandas.DataFrame(kostka.analyze())`
+#[pyfunction]
+#[pyo3(signature = (rules=None))]
+fn analyze<'py>(
+    py: Python<'py>,
+    rules: Option<PyRef<PyRuleset>>,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let rules = rules_or_default(rules);
+    // enumerating the rolls of many dices takes a while
+    let stats = py.detach(|| analyze_all(&rules))?;
+    stats
+        .iter()
+        .map(|stats| {
+            let row = PyDict::new(py);
+            row.set_item("dices", stats.dices)?;
+            row.set_item("outcomes", stats.outcomes)?;
+            row.set_item("busts", stats.busts)?;
+            row.set_item("hot_dices", stats.hot_dices)?;
+            row.set_item("bust_chance", stats.bust_chance())?;
+            row.set_item("hot_dice_chance", stats.hot_dice_chance())?;
+            row.set_item("expected_best", stats.expected_best())?;
+            Ok(row)
+        })
+        .collect()
+}
+
+/// Distribution of the best take of a single roll, one dict per dice count
+/// and value, busts having value 0
+#[pyfunction]
+#[pyo3(signature = (rules=None))]
+fn distribution<'py>(
+    py: Python<'py>,
+    rules: Option<PyRef<PyRuleset>>,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let rules = rules_or_default(rules);
+    let stats = py.detach(|| analyze_all(&rules))?;
+    let mut rows = vec![];
+    for stats in &stats {
+        let busts = std::iter::once((0, stats.busts));
+        for (value, rolls) in busts.chain(stats.best_takes.iter().map(|(&v, &n)| (v, n))) {
+            let row = PyDict::new(py);
+            row.set_item("dices", stats.dices)?;
+            row.set_item("best_take", value)?;
+            row.set_item("rolls", rolls)?;
+            row.set_item("chance", rolls as f64 / stats.outcomes as f64)?;
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+/// The `kostka` Python module
+#[pymodule]
+pub fn kostka(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRuleset>()?;
+    m.add_class::<PyTake>()?;
+    m.add_class::<PyHand>()?;
+    m.add_class::<PyGameState>()?;
+    m.add_class::<PyAction>()?;
+    m.add_class::<PyBot>()?;
+    m.add_class::<PyMatch>()?;
+    m.add_function(wrap_pyfunction!(play_game, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze, m)?)?;
+    m.add_function(wrap_pyfunction!(distribution, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    /// Runs Python `code` with the module imported as `kostka`
+    fn run(code: &CStr) -> PyResult<()> {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "kostka")?;
+            kostka(&module)?;
+            let globals = PyDict::new(py);
+            globals.set_item("kostka", module)?;
+            py.run(code, Some(&globals), None)
+        })
+    }
+
+    #[test]
+    fn hand_takes() {
+        run(c"
+hand = kostka.Hand.from_dices([1, 5, 5])
+best = hand.takes()[0]
+assert best.value == 200, best
+assert best.dices_used == [1, 0, 0, 0, 2, 0]
+assert hand.best_value() == 200
+assert kostka.Hand.from_dices([2, 3], kostka.Ruleset(dices=2)).takes() == []
+d8 = kostka.Hand.from_dices([8, 8, 8], kostka.Ruleset(sides=8))
+assert d8.takes()[0].dices_used == [0, 0, 0, 0, 0, 0, 0, 3]
+assert kostka.Ruleset(hot_dice_bonus=500).hot_dice_bonus == 500
+assert kostka.Ruleset(min_bank=300, piggyback=True).piggyback
+try:
+    kostka.Bot('optimal', kostka.Ruleset(five=0))
+    raise AssertionError('worthless fives accepted')
+except ValueError:
+    pass
+")
+        .unwrap();
+    }
+
+    #[test]
+    fn python_player_plays_a_game() {
+        run(c"
+class Cautious:
+    def __init__(self):
+        self.rounds = 0
+
+    def new_round(self):
+        self.rounds += 1
+
+    def pick_take(self, state, hand):
+        takes = hand.takes()
+        return takes[0] if takes else None
+
+    def continue_or_stop(self, state):
+        return kostka.Action.Stop
+
+teams = kostka.play_game(['greedy'] * 4, goal=300, seed=5, teams=2)
+assert teams['team_scores'][teams['team']] > 300
+assert 'winner' not in teams
+
+me = Cautious()
+summary = kostka.play_game([me, 'optimal', kostka.Bot('greedy')], goal=300, seed=5)
+assert summary['winner'] in (0, 1, 2)
+assert max(summary['scores']) > 300
+assert me.rounds >= summary['rounds']
+")
+        .unwrap();
+    }
+
+    #[test]
+    fn player_exceptions_are_raised() {
+        let e = run(c"
+class Broken:
+    def pick_take(self, state, hand):
+        raise KeyError('oops')
+
+    def continue_or_stop(self, state):
+        return kostka.Action.Stop
+
+kostka.play_game([Broken(), 'greedy'], seed=1)
+")
+        .unwrap_err();
+        Python::attach(|py| assert!(e.is_instance_of::<pyo3::exceptions::PyKeyError>(py)));
+    }
+
+    #[test]
+    fn steps_a_match() {
+        run(c"
+game = kostka.Match(2, goal=300, seed=7)
+while game.winner is None:
+    event = game.step()
+    if event['type'] == 'pick_take':
+        game.take(0 if event['takes'] else None)
+    elif event['type'] == 'continue_or_stop':
+        game.decide(kostka.Action.Stop)
+assert game.state.scores[game.winner] > 300
+
+game = kostka.Match(2, seed=7, rules=kostka.Ruleset(piggyback=True))
+while (event := game.step())['type'] != 'take_over':
+    if event['type'] == 'pick_take':
+        game.take(0 if event['takes'] else None)
+    elif event['type'] == 'continue_or_stop':
+        game.decide(kostka.Action.Stop)
+game.take_over(True)
+assert game.state.turn_score == event['turn_score']
+
+def snapshot(game):
+    state = game.state
+    return (state.player, state.scores, state.turn_score, state.dices_available)
+
+def refused(game):
+    before = snapshot(game)
+    try:
+        game.take(0)
+    except ValueError:
+        return snapshot(game) == before
+    return False
+
+game = kostka.Match(2, seed=7)
+assert refused(game)
+assert game.step()['type'] == 'turn_started'
+event = game.step()
+assert event['type'] == 'pick_take'
+game.take(0 if event['takes'] else None)
+assert refused(game)
+")
+        .unwrap();
+    }
+
+    #[test]
+    fn analysis_rows() {
+        run(c"
+rows = kostka.analyze(kostka.Ruleset(dices=3))
+assert [row['dices'] for row in rows] == [1, 2, 3]
+assert abs(rows[0]['bust_chance'] - 4 / 6) < 1e-9
+chances = [row['chance'] for row in kostka.distribution() if row['dices'] == 6]
+assert abs(sum(chances) - 1) < 1e-9
+")
+        .unwrap();
+    }
+}
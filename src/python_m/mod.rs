@@ -0,0 +1,4 @@
+mod bindings;
+pub use bindings::{kostka, PyAction, PyBot, PyGameState, PyHand, PyMatch, PyRuleset, PyTake};
+
+mod py_player;
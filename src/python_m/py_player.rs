@@ -0,0 +1,103 @@
+use std::{cell::RefCell, rc::Rc};
+
+use pyo3::prelude::*;
+
+use super::bindings::{PyAction, PyGameState, PyHand, PyTake};
+use crate::{
+    dice_m::{Hand, TakeOption},
+    game_m::{Decision, GameAction, GameState},
+};
+
+/// First exception raised by a Python player, shared by the players of a game
+pub(crate) type PyFailure = Rc<RefCell<Option<PyErr>>>;
+
+/// [`Decision`] of a Python object with `pick_take(state, hand)` and
+/// `continue_or_stop(state)` methods, `new_round()` and `new_dices()` are
+/// called when the object has them and `take_over(state)` declines without.
+///
+/// An exception forfeits the player and is kept for the caller to raise.
+pub(crate) struct PyPlayer {
+    brain: Py<PyAny>,
+    failure: PyFailure,
+    forfeited: bool,
+}
+
+impl PyPlayer {
+    pub(crate) fn new(brain: Py<PyAny>, failure: PyFailure) -> Self {
+        PyPlayer {
+            brain,
+            failure,
+            forfeited: false,
+        }
+    }
+
+    fn call_if_defined(&mut self, method: &str) {
+        let called = Python::attach(|py| {
+            let brain = self.brain.bind(py);
+            if brain.hasattr(method)? {
+                brain.call_method0(method)?;
+            }
+            Ok(())
+        });
+        self.check(called);
+    }
+
+    fn check<T>(&mut self, result: PyResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.forfeited = true;
+                self.failure.borrow_mut().get_or_insert(e);
+                None
+            }
+        }
+    }
+}
+
+impl Decision for PyPlayer {
+    fn new_round(&mut self) {
+        self.call_if_defined("new_round");
+    }
+
+    fn new_dices(&mut self) {
+        self.call_if_defined("new_dices");
+    }
+
+    fn pick_take(&mut self, game_state: &GameState, hand: Hand) -> Option<TakeOption> {
+        let picked = Python::attach(|py| {
+            let state = PyGameState::from(game_state.clone());
+            let take = self
+                .brain
+                .call_method1(py, "pick_take", (state, PyHand::from(hand)))?;
+            let take: Option<PyRef<PyTake>> = take.extract(py)?;
+            Ok(take.map(|take| take.take))
+        });
+        self.check(picked).flatten()
+    }
+
+    fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction {
+        let decided = Python::attach(|py| {
+            let state = PyGameState::from(game_state.clone());
+            let action = self.brain.call_method1(py, "continue_or_stop", (state,))?;
+            Ok(action.extract::<PyAction>(py)?.into())
+        });
+        // a forfeited player does not get to act anyway
+        self.check(decided).unwrap_or(GameAction::Stop)
+    }
+
+    fn take_over(&mut self, game_state: &GameState) -> bool {
+        let taken = Python::attach(|py| {
+            let brain = self.brain.bind(py);
+            if !brain.hasattr("take_over")? {
+                return Ok(false);
+            }
+            let state = PyGameState::from(game_state.clone());
+            brain.call_method1("take_over", (state,))?.extract::<bool>()
+        });
+        self.check(taken).unwrap_or(false)
+    }
+
+    fn forfeited(&self) -> bool {
+        self.forfeited
+    }
+}
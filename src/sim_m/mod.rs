@@ -0,0 +1,8 @@
+mod stats;
+pub use stats::{wilson_interval, Distribution};
+
+mod simulation;
+pub use simulation::{simulate, SimConfig, SimReport};
+
+mod tournament;
+pub use tournament::{tournament, TournamentConfig, TournamentReport};
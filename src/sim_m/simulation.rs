@@ -0,0 +1,209 @@
+use std::fmt::Display;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use super::stats::{wilson_interval, Distribution};
+use crate::dice_m::Ruleset;
+use crate::game_m::{Game, MatchResult, Player};
+use crate::KostkaError;
+
+/// Settings of a batch of headless bot games
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub games: u64,
+    /// Bot names from `BOT_NAMES`, in seat order
+    pub players: Vec<String>,
+    pub seed: u64,
+    pub goal: u32,
+    pub rules: Ruleset,
+}
+
+/// Aggregated results of a batch of games
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimReport {
+    pub players: Vec<String>,
+    pub games: u64,
+    /// Games won by every seat, alone or with its team
+    pub wins: Vec<u64>,
+    /// Games that ended with an error
+    pub errors: u64,
+    /// Finished rounds per game
+    pub rounds: Distribution,
+    /// Final scores per seat
+    pub scores: Vec<Distribution>,
+}
+
+impl SimReport {
+    fn new(players: &[String]) -> Self {
+        SimReport {
+            players: players.to_vec(),
+            games: 0,
+            wins: vec![0; players.len()],
+            errors: 0,
+            rounds: Distribution::new(),
+            scores: vec![Distribution::new(); players.len()],
+        }
+    }
+
+    fn add_game(&mut self, game: &Game, result: Result<MatchResult, KostkaError>) {
+        self.games += 1;
+        match result {
+            Ok(MatchResult::Won(i)) => self.wins[i as usize] += 1,
+            // every player of the winning team wins
+            Ok(MatchResult::TeamWon(team)) => {
+                for i in 0..self.wins.len() {
+                    if game.state().team_of(i) == team as usize {
+                        self.wins[i] += 1;
+                    }
+                }
+            }
+            Err(_) => self.errors += 1,
+        }
+        self.rounds.add(game.rounds() as u32);
+        for (dist, &score) in self.scores.iter_mut().zip(game.scores()) {
+            dist.add(score);
+        }
+    }
+
+    fn merge(mut self, other: SimReport) -> Self {
+        self.games += other.games;
+        self.errors += other.errors;
+        for (wins, other_wins) in self.wins.iter_mut().zip(&other.wins) {
+            *wins += other_wins;
+        }
+        self.rounds.merge(&other.rounds);
+        for (dist, other_dist) in self.scores.iter_mut().zip(&other.scores) {
+            dist.merge(other_dist);
+        }
+        self
+    }
+
+    pub fn win_rate(&self, seat: usize) -> f64 {
+        self.wins[seat] as f64 / self.games as f64
+    }
+}
+
+impl Display for SimReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "games: {} | errors: {}", self.games, self.errors)?;
+        writeln!(
+            f,
+            "rounds: {:.2} ± {:.2} (median {}, 90% {})",
+            self.rounds.mean(),
+            self.rounds.mean_ci(),
+            self.rounds.percentile(0.5),
+            self.rounds.percentile(0.9)
+        )?;
+        writeln!(
+            f,
+            "{:>4} {:<10} | {:>7} | {:>17} | {:>13} | {:>6} {:>6} {:>6}",
+            "seat", "player", "win", "95% ci", "score", "10%", "50%", "90%"
+        )?;
+        for (seat, name) in self.players.iter().enumerate() {
+            let (low, high) = wilson_interval(self.wins[seat], self.games);
+            let scores = &self.scores[seat];
+            writeln!(
+                f,
+                "{:>4} {:<10} | {:>6.2}% | {:>6.2}% - {:>6.2}% | {:>7.0} ± {:>3.0} | {:>6} {:>6} {:>6}",
+                seat + 1,
+                name,
+                self.win_rate(seat) * 100.0,
+                low * 100.0,
+                high * 100.0,
+                scores.mean(),
+                scores.mean_ci(),
+                scores.percentile(0.1),
+                scores.percentile(0.5),
+                scores.percentile(0.9)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `config.games` games spread over all cores.
+///
+/// Game `i` rolls from stream `i` of a generator seeded by `config.seed`, so
+/// the report does not depend on the number of threads.
+pub fn simulate(config: &SimConfig) -> Result<SimReport, KostkaError> {
+    (0..config.games)
+        .into_par_iter()
+        .try_fold(
+            || SimReport::new(&config.players),
+            |mut report, i| {
+                let mut game =
+                    bot_game(&config.players, config.goal, &config.rules, config.seed, i)?;
+                let result = game.play();
+                report.add_game(&game, result);
+                Ok(report)
+            },
+        )
+        .try_reduce(|| SimReport::new(&config.players), |a, b| Ok(a.merge(b)))
+}
+
+/// Game between bots by `rules`, rolling from `stream` of the `seed`
+/// generator; errs on unsupported rules and bots that cannot be started
+pub(crate) fn bot_game<S: AsRef<str>>(
+    players: &[S],
+    goal: u32,
+    rules: &Ruleset,
+    seed: u64,
+    stream: u64,
+) -> Result<Game, KostkaError> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+
+    let mut game = Game::with_rng(rng);
+    game.set_limit(goal);
+    game.set_rules(*rules)?;
+    for (seat, kind) in players.iter().map(AsRef::as_ref).enumerate() {
+        let name = format!("{}{}", kind, seat + 1);
+        game.add_player(Player::bot_with_rules(kind, &name, rules)?)?;
+    }
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(games: u64) -> SimConfig {
+        SimConfig {
+            games,
+            players: vec!["greedy".into(), "optimal".into()],
+            seed: 7,
+            goal: 2000,
+            rules: Ruleset::default(),
+        }
+    }
+
+    #[test]
+    fn reproducible() {
+        let first = simulate(&config(200)).unwrap();
+        let second = simulate(&config(200)).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn every_game_counted() {
+        let report = simulate(&config(100)).unwrap();
+
+        assert_eq!(report.games, 100);
+        assert_eq!(report.wins.iter().sum::<u64>() + report.errors, 100);
+        assert_eq!(report.rounds.len(), 100);
+    }
+
+    #[test]
+    fn unsupported_setups_fail() {
+        let mut bad_rules = config(10);
+        bad_rules.rules.dices = 0;
+        assert!(matches!(simulate(&bad_rules), Err(KostkaError::Ruleset(_))));
+
+        let mut bad_bot = config(10);
+        bad_bot.players[1] = "external:".into();
+        assert!(matches!(simulate(&bad_bot), Err(KostkaError::Io(_))));
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+/// z value of the 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// Histogram of integer samples
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Distribution {
+    counts: BTreeMap<u32, u64>,
+    n: u64,
+}
+
+impl Distribution {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add(&mut self, sample: u32) {
+        *self.counts.entry(sample).or_insert(0) += 1;
+        self.n += 1;
+    }
+
+    pub fn merge(&mut self, other: &Distribution) {
+        for (&sample, &n) in other.counts.iter() {
+            *self.counts.entry(sample).or_insert(0) += n;
+        }
+        self.n += other.n;
+    }
+
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Sample -> number of occurrences
+    pub fn counts(&self) -> &BTreeMap<u32, u64> {
+        &self.counts
+    }
+
+    pub fn mean(&self) -> f64 {
+        let sum: f64 = self.counts.iter().map(|(&s, &n)| s as f64 * n as f64).sum();
+        sum / self.n as f64
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        let sq_sum: f64 = self
+            .counts
+            .iter()
+            .map(|(&s, &n)| (s as f64 - mean).powi(2) * n as f64)
+            .sum();
+        (sq_sum / self.n as f64).sqrt()
+    }
+
+    /// Half width of the 95% confidence interval of the mean
+    pub fn mean_ci(&self) -> f64 {
+        Z_95 * self.std_dev() / (self.n as f64).sqrt()
+    }
+
+    /// Smallest sample with at least `p` (0..=1) of samples below or equal
+    pub fn percentile(&self, p: f64) -> u32 {
+        let target = (p * self.n as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (&sample, &n) in self.counts.iter() {
+            seen += n;
+            if seen >= target {
+                return sample;
+            }
+        }
+        self.counts.keys().next_back().copied().unwrap_or(0)
+    }
+}
+
+/// Wilson score 95% interval of a proportion of `k` successes in `n` trials
+pub fn wilson_interval(k: u64, n: u64) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    let n = n as f64;
+    let p = k as f64 / n;
+    let z2 = Z_95 * Z_95;
+
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    (center - half, center + half)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_and_percentiles() {
+        let mut dist = Distribution::new();
+        for sample in 1..=100 {
+            dist.add(sample);
+        }
+
+        assert_eq!(dist.mean(), 50.5);
+        assert_eq!(dist.percentile(0.5), 50);
+        assert_eq!(dist.percentile(0.9), 90);
+        assert_eq!(dist.percentile(1.0), 100);
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = Distribution::new();
+        a.add(1);
+        let mut b = Distribution::new();
+        b.add(1);
+        b.add(3);
+
+        a.merge(&b);
+
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.counts(), &BTreeMap::from([(1, 2), (3, 1)]));
+    }
+
+    #[test]
+    fn wilson_contains_proportion() {
+        let (low, high) = wilson_interval(500, 1000);
+
+        assert!(low < 0.5 && 0.5 < high);
+        assert!(high - low < 0.07);
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt::Display;
+
+use rayon::prelude::*;
+
+use super::simulation::bot_game;
+use crate::dice_m::Ruleset;
+use crate::game_m::MatchResult;
+use crate::KostkaError;
+
+const INITIAL_RATING: f64 = 1500.0;
+
+/// Settings of a round-robin between built-in bots
+#[derive(Debug, Clone)]
+pub struct TournamentConfig {
+    /// Bot names from `BOT_NAMES`
+    pub players: Vec<String>,
+    /// Matches played by every pair of bots
+    pub matches: u64,
+    pub seed: u64,
+    pub goal: u32,
+    /// Elo K-factor
+    pub k_factor: f64,
+    pub rules: Ruleset,
+}
+
+/// Head to head match of two bots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pairing {
+    first: usize,
+    second: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentReport {
+    pub players: Vec<String>,
+    pub ratings: Vec<f64>,
+    /// `wins[i][j]` is the number of games `i` won against `j`
+    pub wins: Vec<Vec<u64>>,
+    /// Games that ended with an error
+    pub errors: u64,
+}
+
+impl TournamentReport {
+    fn new(players: &[String]) -> Self {
+        let n = players.len();
+        TournamentReport {
+            players: players.to_vec(),
+            ratings: vec![INITIAL_RATING; n],
+            wins: vec![vec![0; n]; n],
+            errors: 0,
+        }
+    }
+
+    fn add_match(&mut self, winner: usize, loser: usize, k_factor: f64) {
+        self.wins[winner][loser] += 1;
+
+        let expected =
+            1.0 / (1.0 + 10f64.powf((self.ratings[loser] - self.ratings[winner]) / 400.0));
+        let change = k_factor * (1.0 - expected);
+        self.ratings[winner] += change;
+        self.ratings[loser] -= change;
+    }
+
+    /// Share of the games between `i` and `j` won by `i`
+    pub fn win_rate(&self, i: usize, j: usize) -> Option<f64> {
+        let games = self.wins[i][j] + self.wins[j][i];
+        match games {
+            0 => None,
+            _ => Some(self.wins[i][j] as f64 / games as f64),
+        }
+    }
+
+    /// Player indexes from the best rating
+    pub fn leaderboard(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.players.len()).collect();
+        order.sort_by(|&a, &b| self.ratings[b].total_cmp(&self.ratings[a]));
+        order
+    }
+}
+
+impl Display for TournamentReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>4} {:<10} | {:>6} | {:>8} | {:>6}",
+            "rank", "player", "elo", "won", "win"
+        )?;
+        for (rank, &i) in self.leaderboard().iter().enumerate() {
+            let won: u64 = self.wins[i].iter().sum();
+            let played: u64 = won + self.wins.iter().map(|row| row[i]).sum::<u64>();
+            writeln!(
+                f,
+                "{:>4} {:<10} | {:>6.0} | {:>8} | {:>5.1}%",
+                rank + 1,
+                self.players[i],
+                self.ratings[i],
+                won,
+                won as f64 / played.max(1) as f64 * 100.0
+            )?;
+        }
+
+        writeln!(f)?;
+        write!(f, "{:<10}", "row vs col")?;
+        for name in self.players.iter() {
+            write!(f, " | {:>10}", name)?;
+        }
+        writeln!(f)?;
+        for (i, name) in self.players.iter().enumerate() {
+            write!(f, "{:<10}", name)?;
+            for j in 0..self.players.len() {
+                match self.win_rate(i, j) {
+                    Some(rate) if i != j => write!(f, " | {:>9.1}%", rate * 100.0)?,
+                    _ => write!(f, " | {:>10}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        if self.errors > 0 {
+            writeln!(f, "errors: {}", self.errors)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `config.matches` games between every pair of bots.
+///
+/// Seats alternate between matches of a pair so neither bot profits from
+/// moving first. Games run on all cores, ratings are then updated in match
+/// order so the result is reproducible.
+pub fn tournament(config: &TournamentConfig) -> Result<TournamentReport, KostkaError> {
+    let n = config.players.len();
+    let pairings: Vec<Pairing> = (0..n)
+        .flat_map(|first| ((first + 1)..n).map(move |second| Pairing { first, second }))
+        .collect();
+
+    // one stream per match, interleaving pairs so the ratings move evenly
+    let games: Vec<(Pairing, bool)> = (0..config.matches)
+        .flat_map(|m| pairings.iter().map(move |&pairing| (pairing, m % 2 == 1)))
+        .collect();
+
+    let results: Vec<Option<(usize, usize)>> = games
+        .par_iter()
+        .enumerate()
+        .map(|(stream, &(pairing, swapped))| {
+            let seats = match swapped {
+                false => [pairing.first, pairing.second],
+                true => [pairing.second, pairing.first],
+            };
+            let players = seats.map(|i| config.players[i].as_str());
+            let mut game = bot_game(
+                &players,
+                config.goal,
+                &config.rules,
+                config.seed,
+                stream as u64,
+            )?;
+
+            Ok(match game.play() {
+                Ok(MatchResult::Won(seat)) => {
+                    let winner = seats[seat as usize];
+                    let loser = seats[1 - seat as usize];
+                    Some((winner, loser))
+                }
+                // head to head matches have no teams
+                Ok(MatchResult::TeamWon(_)) | Err(_) => None,
+            })
+        })
+        .collect::<Result<_, KostkaError>>()?;
+
+    let mut report = TournamentReport::new(&config.players);
+    for result in results {
+        match result {
+            Some((winner, loser)) => report.add_match(winner, loser, config.k_factor),
+            None => report.errors += 1,
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> TournamentConfig {
+        TournamentConfig {
+            players: vec!["greedy".into(), "optimal".into()],
+            matches: 100,
+            seed: 3,
+            goal: 2000,
+            k_factor: 16.0,
+            rules: Ruleset::default(),
+        }
+    }
+
+    #[test]
+    fn every_match_counted() {
+        let report = tournament(&config()).unwrap();
+
+        assert_eq!(report.wins[0][1] + report.wins[1][0] + report.errors, 100);
+    }
+
+    #[test]
+    fn ratings_are_zero_sum() {
+        let report = tournament(&config()).unwrap();
+
+        let total: f64 = report.ratings.iter().sum();
+        assert!((total - 2.0 * INITIAL_RATING).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reproducible() {
+        assert_eq!(
+            tournament(&config()).unwrap(),
+            tournament(&config()).unwrap()
+        );
+    }
+
+    #[test]
+    fn elo_moves_towards_winner() {
+        let mut report = TournamentReport::new(&["a".into(), "b".into()]);
+        report.add_match(0, 1, 16.0);
+
+        assert_eq!(report.ratings, vec![1508.0, 1492.0]);
+    }
+}
@@ -0,0 +1,81 @@
+/// Lines of the ASCII art of one dice
+pub const ART_HEIGHT: usize = 5;
+
+/// Columns of the ASCII art of one dice
+pub const ART_WIDTH: usize = 9;
+
+/// Pips of `face` on a 3x3 grid, rows from the top
+fn pips(face: i32) -> [[bool; 3]; 3] {
+    const O: bool = true;
+    const X: bool = false;
+    match face {
+        1 => [[X, X, X], [X, O, X], [X, X, X]],
+        2 => [[O, X, X], [X, X, X], [X, X, O]],
+        3 => [[O, X, X], [X, O, X], [X, X, O]],
+        4 => [[O, X, O], [X, X, X], [O, X, O]],
+        5 => [[O, X, O], [X, O, X], [O, X, O]],
+        6 => [[O, X, O], [O, X, O], [O, X, O]],
+        _ => [[X; 3]; 3],
+    }
+}
+
+/// ASCII art of a dice showing `face`, [`ART_WIDTH`] columns per line.
+///
+/// Faces past 6 show their number instead of pips.
+pub fn dice_art(face: i32) -> [String; ART_HEIGHT] {
+    let row = |pips: [bool; 3]| {
+        let pips: Vec<&str> = pips.iter().map(|&p| if p { "o" } else { " " }).collect();
+        format!("| {} |", pips.join(" "))
+    };
+    let [top, middle, bottom] = pips(face);
+    let middle = match face {
+        1..=6 => row(middle),
+        _ => format!("| {:^5} |", face),
+    };
+    [
+        "+-------+".to_string(),
+        row(top),
+        middle,
+        row(bottom),
+        "+-------+".to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn five() {
+        assert_eq!(
+            dice_art(5),
+            [
+                "+-------+",
+                "| o   o |",
+                "|   o   |",
+                "| o   o |",
+                "+-------+"
+            ]
+        );
+    }
+
+    #[test]
+    fn high_faces_show_their_number() {
+        assert_eq!(dice_art(12)[2], "|  12   |");
+        assert!(dice_art(7).iter().all(|line| line.len() == ART_WIDTH));
+    }
+
+    #[test]
+    fn pips_match_face() {
+        for face in 1..=6 {
+            let art = dice_art(face);
+            let pips = art
+                .iter()
+                .flat_map(|line| line.chars())
+                .filter(|&c| c == 'o');
+
+            assert_eq!(pips.count(), face as usize);
+            assert!(art.iter().all(|line| line.len() == ART_WIDTH));
+        }
+    }
+}
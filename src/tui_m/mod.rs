@@ -0,0 +1,7 @@
+mod dice_art;
+pub use dice_art::{dice_art, ART_HEIGHT, ART_WIDTH};
+
+mod view;
+
+mod tui_player;
+pub use tui_player::{Screen, TuiPlayer};
@@ -0,0 +1,426 @@
+use std::{
+    cell::RefCell,
+    io::{self, stdout},
+    rc::Rc,
+};
+
+use ratatui::{
+    crossterm::{
+        event::{
+            self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind,
+            KeyModifiers, MouseButton, MouseEventKind,
+        },
+        execute,
+    },
+    layout::{Position, Rect},
+    DefaultTerminal,
+};
+
+use super::view::{dice_at, render, Selection, View};
+use crate::{
+    dice_m::{FaceCounts, Hand, TakeOption},
+    game_m::{Decision, GameAction, GameState, PlayerInfo},
+};
+
+const PICK_HELP: &str =
+    "<-/-> move | space or 1-9 toggle | click toggle | b best | enter take | esc leave game";
+const STOP_HELP: &str = "y bank the points | n roll again | esc leave game";
+const TAKE_OVER_HELP: &str = "y take over the dices | n roll all the dices | esc leave game";
+const BUST_HELP: &str = "any key to pass the dices | esc leave game";
+
+/// Full-screen terminal shared by all the players at it.
+///
+/// Restores the terminal when dropped.
+pub struct Screen {
+    terminal: DefaultTerminal,
+    /// Oldest first
+    history: Vec<String>,
+    /// Scoreboard at the last look, to log what the other players did
+    seen: Vec<PlayerInfo>,
+    /// Where the dices were drawn, for mouse clicks
+    dices_area: Rect,
+}
+
+impl Screen {
+    /// Switches the terminal to the alternate screen and captures the mouse
+    pub fn new() -> io::Result<Self> {
+        let terminal = ratatui::try_init()?;
+        execute!(stdout(), EnableMouseCapture)?;
+        Ok(Screen {
+            terminal,
+            history: vec![],
+            seen: vec![],
+            dices_area: Rect::default(),
+        })
+    }
+
+    /// Screen shared by several [`TuiPlayer`]s, e.g. for hot-seat play
+    pub fn shared() -> io::Result<Rc<RefCell<Self>>> {
+        Ok(Rc::new(RefCell::new(Self::new()?)))
+    }
+
+    /// Logs turns ended since the last look at `state`
+    fn observe(&mut self, state: &GameState) {
+        let players: Vec<PlayerInfo> = state.players().collect();
+        for (i, (now, before)) in players.iter().zip(&self.seen).enumerate() {
+            if now.score > before.score {
+                self.history
+                    .push(format!("p{} banked {}", i + 1, now.score - before.score));
+            }
+            if now.bust_streak > before.bust_streak {
+                self.history.push(format!("p{} busted", i + 1));
+            }
+            if now.forfeited && !before.forfeited {
+                self.history.push(format!("p{} left the game", i + 1));
+            }
+        }
+        self.seen = players;
+    }
+
+    fn draw(&mut self, state: &GameState, roll: &Roll, prompt: &str, help: &str) -> io::Result<()> {
+        let view = View {
+            state,
+            dices: &roll.dices,
+            selected: &roll.selected,
+            cursor: roll.cursor,
+            selection: roll.selection.clone(),
+            prompt,
+            help,
+            history: &self.history,
+        };
+        let mut dices_area = Rect::default();
+        self.terminal
+            .draw(|frame| dices_area = render(frame, &view))?;
+        self.dices_area = dices_area;
+        Ok(())
+    }
+
+    /// Lets the player toggle dices until they take a legal selection
+    fn pick(&mut self, state: &GameState, hand: &Hand) -> io::Result<Answer<TakeOption>> {
+        let mut roll = Roll::new(hand);
+        loop {
+            roll.selection = match selected_take(hand, &roll.selected) {
+                Ok(None) => Selection::Empty,
+                Ok(Some(take)) => Selection::Legal(describe(hand, &take)),
+                Err(reason) => Selection::Illegal(reason),
+            };
+            self.draw(state, &roll, "Pick dices to take", PICK_HELP)?;
+
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Left => roll.move_cursor(-1),
+                    KeyCode::Right => roll.move_cursor(1),
+                    KeyCode::Char(' ') => roll.toggle(roll.cursor.unwrap_or(0)),
+                    KeyCode::Char(c @ '1'..='9') => roll.toggle(c as usize - '1' as usize),
+                    KeyCode::Char('b') => roll.select(hand, &hand.takes()[0]),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(Answer::Leave)
+                    }
+                    KeyCode::Esc => return Ok(Answer::Leave),
+                    KeyCode::Enter => {
+                        if let Ok(Some(take)) = selected_take(hand, &roll.selected) {
+                            self.history.push(format!(
+                                "p{} took {}",
+                                state.my_index() + 1,
+                                describe(hand, &take)
+                            ));
+                            return Ok(Answer::Give(take));
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    let position = Position::new(mouse.column, mouse.row);
+                    if let Some(i) = dice_at(self.dices_area, position, roll.dices.len()) {
+                        roll.cursor = Some(i);
+                        roll.toggle(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Shows the roll without takes until the player acknowledges it
+    fn bust(&mut self, state: &GameState, hand: &Hand) -> io::Result<Answer<()>> {
+        let mut roll = Roll::new(hand);
+        roll.cursor = None;
+        roll.selection = Selection::Illegal("no scoring dices");
+        self.draw(state, &roll, "Bust! The turn score is lost", BUST_HELP)?;
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    return Ok(match key.code {
+                        KeyCode::Esc => Answer::Leave,
+                        _ => Answer::Give(()),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Asks whether to bank the turn score, `roll` is the last roll taken from
+    fn stop(&mut self, state: &GameState, roll: &Roll) -> io::Result<Answer<GameAction>> {
+        let prompt = format!(
+            "Bank {} points or roll {} dices?",
+            state.turn_score(),
+            state.dices_available()
+        );
+        self.draw(state, roll, &prompt, STOP_HELP)?;
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('y') => return Ok(Answer::Give(GameAction::Stop)),
+                    KeyCode::Char('n') => return Ok(Answer::Give(GameAction::Continue)),
+                    KeyCode::Esc => return Ok(Answer::Leave),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Asks whether to start from the `score` and `dices` the previous
+    /// player banked with
+    fn take_over(
+        &mut self,
+        state: &GameState,
+        score: u32,
+        dices: usize,
+    ) -> io::Result<Answer<bool>> {
+        let prompt = format!("Take over {} points and roll {} dices?", score, dices);
+        self.draw(state, &Roll::default(), &prompt, TAKE_OVER_HELP)?;
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('y') => return Ok(Answer::Give(true)),
+                    KeyCode::Char('n') => return Ok(Answer::Give(false)),
+                    KeyCode::Esc => return Ok(Answer::Leave),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), DisableMouseCapture);
+        ratatui::restore();
+    }
+}
+
+/// What the player at the screen answered
+enum Answer<T> {
+    Give(T),
+    /// Leave the game
+    Leave,
+}
+
+/// Dices on the screen and the player's selection of them
+#[derive(Debug, Clone, Default)]
+struct Roll {
+    dices: Vec<i32>,
+    selected: Vec<bool>,
+    cursor: Option<usize>,
+    selection: Selection,
+}
+
+impl Roll {
+    fn new(hand: &Hand) -> Self {
+        let dices: Vec<i32> = hand.get_dices().iter().map(|dice| dice.value).collect();
+        Roll {
+            selected: vec![false; dices.len()],
+            dices,
+            cursor: Some(0),
+            selection: Selection::Empty,
+        }
+    }
+
+    fn toggle(&mut self, i: usize) {
+        if let Some(selected) = self.selected.get_mut(i) {
+            *selected = !*selected;
+        }
+    }
+
+    fn move_cursor(&mut self, by: isize) {
+        let last = self.dices.len().saturating_sub(1) as isize;
+        let cursor = self.cursor.unwrap_or(0) as isize + by;
+        self.cursor = Some(cursor.clamp(0, last) as usize);
+    }
+
+    /// Selects the dices of `take`
+    fn select(&mut self, hand: &Hand, take: &TakeOption) {
+        let mut left = take.dices_used;
+        for (selected, dice) in self.selected.iter_mut().zip(hand.get_dices()) {
+            let n = &mut left[dice.value as usize - 1];
+            *selected = *n > 0;
+            *n = n.saturating_sub(1);
+        }
+    }
+}
+
+/// Best take using exactly the `selected` dices of `hand`.
+///
+/// Errs with the reason when they do not form a take or leave scoring dices
+/// while all the dices could be taken.
+pub(crate) fn selected_take(
+    hand: &Hand,
+    selected: &[bool],
+) -> Result<Option<TakeOption>, &'static str> {
+    let mut counts = FaceCounts::new(hand.rules().sides);
+    let chosen = hand.get_dices().iter().zip(selected).filter(|(_, &s)| s);
+    for (dice, _) in chosen {
+        counts[dice.value as usize - 1] += 1;
+    }
+    if counts.total() == 0 {
+        return Ok(None);
+    }
+
+    let take = hand
+        .takes()
+        .iter()
+        .find(|take| take.dices_used == counts)
+        .ok_or("not a scoring take")?;
+    if take.dices_count() < hand.dices_used() && !hand.takes_use_all().is_empty() {
+        return Err("all the dices score, take them all");
+    }
+    Ok(Some(*take))
+}
+
+fn describe(hand: &Hand, take: &TakeOption) -> String {
+    match hand.breakdown(take) {
+        Some(breakdown) => format!("{} = {}", breakdown, take.value),
+        None => take.value.to_string(),
+    }
+}
+
+/// Human player picking takes on a full-screen [`Screen`] by toggling dices
+/// with the keyboard or the mouse
+pub struct TuiPlayer {
+    screen: Rc<RefCell<Screen>>,
+    /// Last roll taken from, shown when asked to stop
+    last_roll: Roll,
+    forfeited: bool,
+}
+
+impl TuiPlayer {
+    pub fn new(screen: Rc<RefCell<Screen>>) -> Self {
+        TuiPlayer {
+            screen,
+            last_roll: Roll::default(),
+            forfeited: false,
+        }
+    }
+
+    /// Value of the answer, leaving the game on I/O errors too
+    fn answered<T>(&mut self, answer: io::Result<Answer<T>>) -> Option<T> {
+        match answer {
+            Ok(Answer::Give(value)) => Some(value),
+            Ok(Answer::Leave) | Err(_) => {
+                self.forfeited = true;
+                None
+            }
+        }
+    }
+}
+
+impl Decision for TuiPlayer {
+    fn pick_take(&mut self, game_state: &GameState, hand: Hand) -> Option<TakeOption> {
+        let screen = self.screen.clone();
+        let mut screen = screen.borrow_mut();
+        screen.observe(game_state);
+
+        if hand.takes().is_empty() {
+            let answer = screen.bust(game_state, &hand);
+            self.answered(answer);
+            return None;
+        }
+
+        let answer = screen.pick(game_state, &hand);
+        let take = self.answered(answer)?;
+        self.last_roll = Roll::new(&hand);
+        self.last_roll.cursor = None;
+        self.last_roll.select(&hand, &take);
+        self.last_roll.selection = Selection::Legal(describe(&hand, &take));
+        Some(take)
+    }
+
+    fn continue_or_stop(&mut self, game_state: &GameState) -> GameAction {
+        let screen = self.screen.clone();
+        let answer = screen.borrow_mut().stop(game_state, &self.last_roll);
+        self.answered(answer).unwrap_or(GameAction::Stop)
+    }
+
+    fn take_over(&mut self, game_state: &GameState) -> bool {
+        let Some((score, dices)) = game_state.previous_turn() else {
+            return false;
+        };
+        let screen = self.screen.clone();
+        let mut screen = screen.borrow_mut();
+        screen.observe(game_state);
+        let answer = screen.take_over(game_state, score, dices);
+        self.answered(answer).unwrap_or(false)
+    }
+
+    fn new_round(&mut self) {}
+
+    fn new_dices(&mut self) {}
+
+    fn forfeited(&self) -> bool {
+        self.forfeited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice_m::Dices;
+
+    fn hand(dices: &str) -> Hand {
+        Hand::from_dices(dices.parse::<Dices>().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn selection_legality() {
+        let hand = hand("2 3 5 5 5 1");
+
+        assert_eq!(selected_take(&hand, &[false; 6]), Ok(None));
+        let take = selected_take(&hand, &[false, false, true, true, true, false]).unwrap();
+        assert_eq!(take.map(|t| t.value), Some(500));
+        let take = selected_take(&hand, &[false, false, true, false, false, true]).unwrap();
+        assert_eq!(take.map(|t| t.value), Some(150));
+        assert_eq!(
+            selected_take(&hand, &[true, false, false, false, false, true]),
+            Err("not a scoring take")
+        );
+    }
+
+    #[test]
+    fn hot_dices_must_be_taken() {
+        let hand = hand("1 5 5");
+
+        assert_eq!(
+            selected_take(&hand, &[true, false, false]),
+            Err("all the dices score, take them all")
+        );
+        let take = selected_take(&hand, &[true; 3]).unwrap();
+        assert_eq!(take.map(|t| t.value), Some(200));
+    }
+
+    #[test]
+    fn best_take_selects_its_dices() {
+        let hand = hand("5 2 1 5 3 4");
+        let mut roll = Roll::new(&hand);
+
+        roll.select(&hand, &hand.takes()[0]);
+
+        let take = selected_take(&hand, &roll.selected).unwrap().unwrap();
+        assert_eq!(take, hand.takes()[0]);
+    }
+}
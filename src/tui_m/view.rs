@@ -0,0 +1,252 @@
+use ratatui::{
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, Paragraph, Row, Table},
+    Frame,
+};
+
+use super::dice_art::{dice_art, ART_HEIGHT, ART_WIDTH};
+use crate::game_m::GameState;
+
+/// Columns between two dices
+const DICE_GAP: u16 = 1;
+
+/// What the selected dices would take
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) enum Selection {
+    #[default]
+    Empty,
+    /// Breakdown of the take
+    Legal(String),
+    /// Why the dices cannot be taken
+    Illegal(&'static str),
+}
+
+/// Everything drawn on one screen
+pub(crate) struct View<'a> {
+    pub state: &'a GameState,
+    pub dices: &'a [i32],
+    pub selected: &'a [bool],
+    /// Dice under the keyboard cursor, None when not picking
+    pub cursor: Option<usize>,
+    pub selection: Selection,
+    /// Question or message above the keys help
+    pub prompt: &'a str,
+    pub help: &'a str,
+    /// Oldest first
+    pub history: &'a [String],
+}
+
+/// Draws `view`, returns the area of the dices for mouse clicks
+pub(crate) fn render(frame: &mut Frame, view: &View) -> Rect {
+    let [status, main, history, help] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Length(ART_HEIGHT as u16 + 6),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [roll, scoreboard] =
+        Layout::horizontal([Constraint::Min(30), Constraint::Length(30)]).areas(main);
+
+    let state = view.state;
+    let mut status_line = format!(
+        "p{} on turn | round {} | turn score {} | goal {}",
+        state.my_index() + 1,
+        state.round() + 1,
+        state.turn_score(),
+        state.goal()
+    );
+    if state.in_teams() {
+        status_line += &format!(" | team score {}", state.my_team_score());
+    }
+    if state.hot_dices() > 0 {
+        status_line += &format!(" | hot dice x{}", state.hot_dices());
+    }
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(status_line),
+            Line::styled(view.prompt, Style::new().add_modifier(Modifier::BOLD)),
+        ])
+        .block(Block::bordered().title(" Kostky ")),
+        status,
+    );
+
+    let roll_block = Block::bordered().title(format!(" Roll of {} dices ", view.dices.len()));
+    let inner = roll_block.inner(roll);
+    frame.render_widget(roll_block, roll);
+    frame.render_widget(Paragraph::new(roll_lines(view)), inner);
+
+    frame.render_widget(scoreboard_table(state), scoreboard);
+
+    let skipped = view
+        .history
+        .len()
+        .saturating_sub(history.height.saturating_sub(2) as usize);
+    let items: Vec<ListItem> = view.history[skipped..]
+        .iter()
+        .map(|line| ListItem::new(line.as_str()))
+        .collect();
+    frame.render_widget(
+        List::new(items).block(Block::bordered().title(" History ")),
+        history,
+    );
+
+    frame.render_widget(
+        Paragraph::new(view.help).style(Style::new().fg(Color::DarkGray)),
+        help,
+    );
+
+    Rect {
+        height: inner.height.min(ART_HEIGHT as u16),
+        ..inner
+    }
+}
+
+/// Index of the dice at `position` inside the dices `area`
+pub(crate) fn dice_at(area: Rect, position: Position, dices: usize) -> Option<usize> {
+    if !area.contains(position) {
+        return None;
+    }
+    let step = ART_WIDTH as u16 + DICE_GAP;
+    let offset = position.x - area.x;
+    let index = (offset / step) as usize;
+    (offset % step < ART_WIDTH as u16 && index < dices).then_some(index)
+}
+
+fn roll_lines<'a>(view: &View) -> Vec<Line<'a>> {
+    let arts: Vec<_> = view.dices.iter().map(|&face| dice_art(face)).collect();
+    let gap = " ".repeat(DICE_GAP as usize);
+
+    let mut lines: Vec<Line> = (0..ART_HEIGHT)
+        .map(|row| {
+            let spans = arts.iter().enumerate().flat_map(|(i, art)| {
+                let style = if view.selected.get(i).copied().unwrap_or(false) {
+                    Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::new()
+                };
+                [
+                    Span::styled(art[row].clone(), style),
+                    Span::raw(gap.clone()),
+                ]
+            });
+            Line::from(spans.collect::<Vec<_>>())
+        })
+        .collect();
+
+    let markers: String = (0..view.dices.len())
+        .map(|i| {
+            let marker = if view.cursor == Some(i) { "^" } else { " " };
+            format!("{:^width$}{}", marker, gap, width = ART_WIDTH)
+        })
+        .collect();
+    lines.push(Line::from(markers));
+
+    lines.push(match &view.selection {
+        Selection::Empty => Line::from(""),
+        Selection::Legal(breakdown) => {
+            Line::styled(breakdown.clone(), Style::new().fg(Color::Green))
+        }
+        Selection::Illegal(reason) => Line::styled(*reason, Style::new().fg(Color::Red)),
+    });
+    lines
+}
+
+fn scoreboard_table(state: &GameState) -> Table<'static> {
+    let rows = state.players().enumerate().map(|(i, player)| {
+        let note = if player.forfeited {
+            "left"
+        } else if !player.entered {
+            "not entered"
+        } else {
+            ""
+        };
+        let name = match state.in_teams() {
+            true => format!("p{} t{}", i + 1, player.team + 1),
+            false => format!("p{}", i + 1),
+        };
+        let row = Row::new(vec![name, player.score.to_string(), note.to_string()]);
+        if i == state.my_index() {
+            row.style(Style::new().add_modifier(Modifier::REVERSED))
+        } else {
+            row
+        }
+    });
+
+    let name_width = if state.in_teams() { 6 } else { 4 };
+    Table::new(
+        rows,
+        [
+            Constraint::Length(name_width),
+            Constraint::Length(7),
+            Constraint::Min(0),
+        ],
+    )
+    .header(Row::new(["", "score", ""]).style(Style::new().add_modifier(Modifier::BOLD)))
+    .block(Block::bordered().title(" Scoreboard "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn screen_text(view: &View) -> (String, Rect) {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut dices_area = Rect::default();
+        terminal
+            .draw(|frame| dices_area = render(frame, view))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text = buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (text, dices_area)
+    }
+
+    #[test]
+    fn draws_roll_and_scoreboard() {
+        let mut state = GameState::with_goal(2000);
+        state.add_player();
+        state.add_player();
+        let history = vec!["p2 banked 350".to_string()];
+        let view = View {
+            state: &state,
+            dices: &[1, 5, 3],
+            selected: &[true, false, false],
+            cursor: Some(1),
+            selection: Selection::Legal("one 1 (100)".into()),
+            prompt: "Pick dices to take",
+            help: "enter take",
+            history: &history,
+        };
+
+        let (text, dices_area) = screen_text(&view);
+
+        assert!(text.contains("p1 on turn | round 1 | turn score 0 | goal 2000"));
+        assert!(text.contains("|   o   | |   o   | |   o   |"));
+        assert!(text.contains("Pick dices to take"));
+        assert!(text.contains("one 1 (100)"));
+        assert!(text.contains("p2 banked 350"));
+        assert!(text.contains("not entered"));
+        assert_eq!(dices_area.height, ART_HEIGHT as u16);
+    }
+
+    #[test]
+    fn clicks_hit_dices() {
+        let area = Rect::new(2, 5, 60, 5);
+
+        assert_eq!(dice_at(area, Position::new(2, 5), 3), Some(0));
+        assert_eq!(dice_at(area, Position::new(12, 9), 3), Some(1));
+        // gap between the first two dices
+        assert_eq!(dice_at(area, Position::new(11, 6), 3), None);
+        assert_eq!(dice_at(area, Position::new(35, 6), 3), None);
+        assert_eq!(dice_at(area, Position::new(2, 10), 3), None);
+    }
+}
@@ -0,0 +1,242 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    dice_m::{Dices, FaceCounts, Hand, Ruleset, TakeOption},
+    game_m::{GameAction, Match, MatchResult, Step, TurnResult},
+    KostkaError,
+};
+
+/// Rules from a plain object such as `{dices: 5}`, the default rules for
+/// `undefined`
+fn rules(value: JsValue) -> Result<Ruleset, JsError> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(Ruleset::default());
+    }
+    let rules: Ruleset = serde_wasm_bindgen::from_value(value)?;
+    rules.validate()?;
+    Ok(rules)
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    // plain objects rather than ES maps
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(value.serialize(&serializer)?)
+}
+
+/// Take as seen from JavaScript
+#[derive(Serialize)]
+struct TakeView {
+    dices_used: FaceCounts,
+    value: u32,
+    /// e.g. `one 1 (100) + three 5s (500)`
+    breakdown: String,
+}
+
+fn take_views(hand: &Hand) -> Vec<TakeView> {
+    hand.takes()
+        .iter()
+        .map(|take| TakeView {
+            dices_used: take.dices_used,
+            value: take.value,
+            breakdown: hand
+                .breakdown(take)
+                .map(|breakdown| breakdown.to_string())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn dice_values(hand: &Hand) -> Vec<i32> {
+    hand.get_dices().iter().map(|dice| dice.value).collect()
+}
+
+/// Roll and its takes, best first
+#[wasm_bindgen(js_name = Hand)]
+pub struct JsHand {
+    hand: Hand,
+}
+
+#[wasm_bindgen(js_class = Hand)]
+impl JsHand {
+    /// Hand of known dices, e.g. `Hand.fromDices([1, 5, 5], {dices: 3})`
+    #[wasm_bindgen(js_name = fromDices)]
+    pub fn from_dices(dices: Vec<i32>, rules: JsValue) -> Result<JsHand, JsError> {
+        let rules = self::rules(rules)?;
+        let hand = Hand::from_dices_with_rules(Dices::from(dices.as_slice()), &rules)?;
+        Ok(JsHand { hand })
+    }
+
+    pub fn dices(&self) -> Vec<i32> {
+        dice_values(&self.hand)
+    }
+
+    /// Array of `{dices_used, value, breakdown}`, `dices_used` counting the
+    /// dices of every face, one count per side of the dice
+    pub fn takes(&self) -> Result<JsValue, JsError> {
+        to_js(&take_views(&self.hand))
+    }
+
+    /// Value of the best take, 0 for a bust
+    #[wasm_bindgen(js_name = bestValue)]
+    pub fn best_value(&self) -> u32 {
+        self.hand.takes().first().map_or(0, |take| take.value)
+    }
+}
+
+/// [`Step`] as seen from JavaScript, tagged by `type`
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StepView {
+    TurnStarted {
+        player: usize,
+    },
+    /// Answer with `take(index)` or `take()` to give up the turn
+    PickTake {
+        player: usize,
+        dices: Vec<i32>,
+        takes: Vec<TakeView>,
+    },
+    /// The previous player banked `turn_score` leaving `dices_available`,
+    /// answer with `takeOver(accept)`
+    TakeOver {
+        player: usize,
+        turn_score: u32,
+        dices_available: usize,
+    },
+    /// Answer with `decide(stop)`
+    ContinueOrStop {
+        player: usize,
+        turn_score: u32,
+        dices_available: usize,
+    },
+    /// `streak` counts the hot dice of the turn
+    HotDice {
+        player: usize,
+        streak: u32,
+    },
+    TurnEnded {
+        player: usize,
+        result: TurnResult,
+    },
+    /// `winner` is the winning player, `team` the winning team when
+    /// playing in teams
+    Finished {
+        winner: Option<usize>,
+        team: Option<usize>,
+    },
+}
+
+/// Game engine stepped from JavaScript, the page asks its players
+#[wasm_bindgen(js_name = Match)]
+pub struct JsMatch {
+    game: Match,
+}
+
+#[wasm_bindgen(js_class = Match)]
+impl JsMatch {
+    /// Match of `players` to `goal` points, rolling from the 32 bytes of
+    /// `seed`, e.g. `crypto.getRandomValues(new Uint8Array(32))`
+    #[wasm_bindgen(constructor)]
+    pub fn new(players: usize, goal: u32, seed: &[u8], rules: JsValue) -> Result<JsMatch, JsError> {
+        let seed: [u8; 32] = seed
+            .try_into()
+            .map_err(|_| JsError::new("seed must have 32 bytes"))?;
+        let mut game = Match::with_rng(ChaCha8Rng::from_seed(seed));
+        game.set_rules(self::rules(rules)?)?;
+        game.set_limit(goal);
+        for _ in 0..players {
+            game.add_player()?;
+        }
+        Ok(JsMatch { game })
+    }
+
+    /// Next event or the unanswered question, see [`Match::step`]
+    pub fn step(&mut self) -> Result<JsValue, JsError> {
+        let player = self.game.state().my_index();
+        let view = match self.game.step()? {
+            Step::TurnStarted(player) => StepView::TurnStarted { player },
+            Step::PickTake(hand) => StepView::PickTake {
+                player,
+                dices: dice_values(&hand),
+                takes: take_views(&hand),
+            },
+            Step::TakeOver => {
+                let (turn_score, dices_available) =
+                    self.game.state().previous_turn().unwrap_or_default();
+                StepView::TakeOver {
+                    player,
+                    turn_score,
+                    dices_available,
+                }
+            }
+            Step::ContinueOrStop => StepView::ContinueOrStop {
+                player,
+                turn_score: self.game.state().turn_score(),
+                dices_available: self.game.state().dices_available(),
+            },
+            Step::HotDice(streak) => StepView::HotDice { player, streak },
+            Step::TurnEnded(player, result) => StepView::TurnEnded { player, result },
+            Step::Finished(MatchResult::Won(winner)) => StepView::Finished {
+                winner: Some(winner as usize),
+                team: None,
+            },
+            Step::Finished(MatchResult::TeamWon(team)) => StepView::Finished {
+                winner: None,
+                team: Some(team as usize),
+            },
+        };
+        to_js(&view)
+    }
+
+    /// Picks the take at `index` of the asked `pick_take`, nothing gives up
+    /// the turn
+    pub fn take(&mut self, index: Option<usize>) -> Result<(), JsError> {
+        let Some(hand) = self.game.asked_hand() else {
+            return Err(KostkaError::NotAsked.into());
+        };
+        let take: Option<TakeOption> = match index {
+            Some(i) => Some(
+                *hand
+                    .takes()
+                    .get(i)
+                    .ok_or_else(|| JsError::new("no take at the index"))?,
+            ),
+            None => None,
+        };
+        Ok(self.game.take(take)?)
+    }
+
+    /// Answers `continue_or_stop`, true banks the turn score
+    pub fn decide(&mut self, stop: bool) -> Result<(), JsError> {
+        let action = match stop {
+            true => GameAction::Stop,
+            false => GameAction::Continue,
+        };
+        Ok(self.game.decide(action)?)
+    }
+
+    /// Splits the players into `teams` sharing their scores, player `i`
+    /// playing for team `i % teams`, no player joins afterwards
+    #[wasm_bindgen(js_name = setTeams)]
+    pub fn set_teams(&mut self, teams: usize) -> Result<(), JsError> {
+        Ok(self.game.set_teams(teams)?)
+    }
+
+    /// Answers `take_over`, true starts from the offered turn
+    #[wasm_bindgen(js_name = takeOver)]
+    pub fn take_over(&mut self, accept: bool) -> Result<(), JsError> {
+        Ok(self.game.take_over(accept)?)
+    }
+
+    pub fn forfeit(&mut self, player: usize) -> Result<(), JsError> {
+        Ok(self.game.forfeit(player)?)
+    }
+
+    /// Scores, goal and turn of the game as a plain object
+    pub fn state(&self) -> Result<JsValue, JsError> {
+        to_js(self.game.state())
+    }
+}
@@ -0,0 +1,604 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::{
+    dice_m::{Ruleset, TakeOption},
+    game_m::{
+        Decision, GameAction, GameState, GreedyPlayer, Match, MatchResult, OptimalPlayer, Step,
+        TurnResult,
+    },
+    net_m::{Action, ClientMessage},
+    KostkaError,
+};
+
+/// Most seats of one game
+pub const MAX_SEATS: usize = 8;
+
+/// Events kept for subscribers that fall behind
+const EVENTS_CAPACITY: usize = 256;
+
+/// Body of a request creating a game
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewGame {
+    /// `open` for a human player or a built-in bot, in seat order
+    pub seats: Vec<String>,
+    #[serde(default = "default_goal")]
+    pub goal: u32,
+    #[serde(default)]
+    pub rules: Ruleset,
+    /// Teams sharing their scores, seat `i` playing for team `i % teams`;
+    /// the game then ends with a winning team
+    #[serde(default)]
+    pub teams: Option<usize>,
+}
+
+fn default_goal() -> u32 {
+    500
+}
+
+/// Seat given to a joining player, the token proves it later
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Joined {
+    pub seat: usize,
+    pub token: String,
+}
+
+/// Lobby entry of a game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: u64,
+    /// `open`, `human` or the bot's name
+    pub seats: Vec<String>,
+    pub goal: u32,
+    pub started: bool,
+    /// Winning seat once finished, None too in teams or when the game failed
+    pub winner: Option<usize>,
+    /// Winning team once a game in teams finished
+    pub team: Option<usize>,
+    pub finished: bool,
+}
+
+/// Events streamed to everybody watching a game, tagged by `type`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// First event of every stream
+    Welcome {
+        /// The subscriber's seat, None for spectators
+        seat: Option<usize>,
+        game: GameSummary,
+        state: GameState,
+    },
+    Started,
+    TurnStarted {
+        player: usize,
+    },
+    /// Answer with `{"type": "pick", "take": <index into takes>}`
+    PickTake {
+        player: usize,
+        dices: Vec<i32>,
+        takes: Vec<TakeOption>,
+    },
+    /// Answer with `{"type": "action", "action": "continue"}` or `"stop"`
+    ContinueOrStop {
+        player: usize,
+        turn_score: u32,
+        dices_available: usize,
+    },
+    /// The previous player banked `turn_score` leaving `dices_available`,
+    /// answer with `{"type": "take_over", "accept": true}` to start from them
+    TakeOver {
+        player: usize,
+        turn_score: u32,
+        dices_available: usize,
+    },
+    /// `streak` counts the hot dice of the turn
+    HotDice {
+        player: usize,
+        streak: u32,
+    },
+    TurnEnded {
+        player: usize,
+        result: TurnResult,
+        state: GameState,
+    },
+    /// `winner` is the winning seat, `team` the winning team of a game in
+    /// teams, neither when the game failed
+    GameOver {
+        winner: Option<usize>,
+        team: Option<usize>,
+    },
+    /// Sent only to the subscriber whose message failed
+    Error {
+        message: String,
+    },
+}
+
+enum Seat {
+    Open,
+    Human {
+        token: String,
+    },
+    Bot {
+        kind: String,
+        brain: Box<dyn Decision + Send>,
+    },
+}
+
+impl Seat {
+    fn name(&self) -> &str {
+        match self {
+            Seat::Open => "open",
+            Seat::Human { .. } => "human",
+            Seat::Bot { kind, .. } => kind,
+        }
+    }
+}
+
+/// Built-in bots a game may seat, external bots would run commands from
+/// the network
+fn bot(kind: &str, rules: &Ruleset) -> Result<Box<dyn Decision + Send>, KostkaError> {
+    match kind {
+        "greedy" => Ok(Box::new(GreedyPlayer::new())),
+        "optimal" => Ok(Box::new(OptimalPlayer::with_rules(rules)?)),
+        _ => Err(KostkaError::UnknownPlayer(kind.into())),
+    }
+}
+
+/// One hosted game, started once every seat is taken
+pub struct Room {
+    id: u64,
+    game: Match,
+    seats: Vec<Seat>,
+    events: broadcast::Sender<String>,
+    /// Question waiting for an answer, repeated to new subscribers
+    question: Option<Event>,
+    started: bool,
+    /// Result once finished, None when the game failed
+    finished: Option<Option<MatchResult>>,
+}
+
+/// Winning seat and winning team of `result`
+fn winners(result: Option<MatchResult>) -> (Option<usize>, Option<usize>) {
+    match result {
+        Some(MatchResult::Won(seat)) => (Some(seat as usize), None),
+        Some(MatchResult::TeamWon(team)) => (None, Some(team as usize)),
+        None => (None, None),
+    }
+}
+
+impl Room {
+    fn new(id: u64, config: &NewGame) -> Result<Self, KostkaError> {
+        if !(1..=MAX_SEATS).contains(&config.seats.len()) {
+            return Err(KostkaError::Refused(format!(
+                "a game has 1 to {} seats",
+                MAX_SEATS
+            )));
+        }
+        let mut game = Match::new();
+        game.set_rules(config.rules)?;
+        game.set_limit(config.goal);
+
+        let seats = config
+            .seats
+            .iter()
+            .map(|kind| match kind.as_str() {
+                "open" => Ok(Seat::Open),
+                _ => bot(kind, &config.rules).map(|brain| Seat::Bot {
+                    kind: kind.clone(),
+                    brain,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for _ in &seats {
+            game.add_player()?;
+        }
+        if let Some(teams) = config.teams {
+            game.set_teams(teams)?;
+        }
+
+        let mut room = Room {
+            id,
+            game,
+            seats,
+            events: broadcast::channel(EVENTS_CAPACITY).0,
+            question: None,
+            started: false,
+            finished: None,
+        };
+        room.start_when_full();
+        Ok(room)
+    }
+
+    pub fn summary(&self) -> GameSummary {
+        let (winner, team) = winners(self.finished.flatten());
+        GameSummary {
+            id: self.id,
+            seats: self.seats.iter().map(|seat| seat.name().into()).collect(),
+            goal: self.game.state().goal(),
+            started: self.started,
+            winner,
+            team,
+            finished: self.finished.is_some(),
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        self.game.state()
+    }
+
+    /// Seats a human player in the first open seat
+    pub fn join(&mut self) -> Result<Joined, KostkaError> {
+        let seat = self
+            .seats
+            .iter()
+            .position(|seat| matches!(seat, Seat::Open))
+            .ok_or_else(|| KostkaError::Refused("no open seat".into()))?;
+        let token = format!("{:016x}", rand::random::<u64>());
+        self.seats[seat] = Seat::Human {
+            token: token.clone(),
+        };
+        self.start_when_full();
+        Ok(Joined { seat, token })
+    }
+
+    /// Seat of the player holding `token`
+    pub fn seat_of(&self, token: &str) -> Option<usize> {
+        self.seats
+            .iter()
+            .position(|seat| matches!(seat, Seat::Human { token: t } if t == token))
+    }
+
+    /// Events so far to catch up on and the stream of the next ones
+    pub fn subscribe(&self, seat: Option<usize>) -> (Vec<Event>, broadcast::Receiver<String>) {
+        let welcome = Event::Welcome {
+            seat,
+            game: self.summary(),
+            state: self.state().clone(),
+        };
+        let mut events = vec![welcome];
+        events.extend(self.question.clone());
+        if let Some(result) = self.finished {
+            let (winner, team) = winners(result);
+            events.push(Event::GameOver { winner, team });
+        }
+        (events, self.events.subscribe())
+    }
+
+    /// Handles a message of the player at `seat`
+    pub fn answer(&mut self, seat: usize, message: ClientMessage) -> Result<(), KostkaError> {
+        if message == ClientMessage::Leave {
+            return self.leave(seat);
+        }
+        if !self.started || self.finished.is_some() || seat != self.state().my_index() {
+            return Err(KostkaError::NotAsked);
+        }
+
+        match (message, &self.question) {
+            (ClientMessage::Pick { take }, Some(Event::PickTake { takes, .. })) => {
+                let take = match take {
+                    Some(i) => Some(
+                        *takes
+                            .get(i)
+                            .ok_or_else(|| KostkaError::Refused(format!("no take {}", i)))?,
+                    ),
+                    None => None,
+                };
+                self.game.take(take)?;
+            }
+            (ClientMessage::Action { action }, Some(Event::ContinueOrStop { .. })) => {
+                self.game.decide(match action {
+                    Action::Continue => GameAction::Continue,
+                    Action::Stop => GameAction::Stop,
+                })?;
+            }
+            (ClientMessage::TakeOver { accept }, Some(Event::TakeOver { .. })) => {
+                self.game.take_over(accept)?;
+            }
+            _ => return Err(KostkaError::NotAsked),
+        }
+        self.question = None;
+        self.advance();
+        Ok(())
+    }
+
+    /// Frees the seat before the start, forfeits after it
+    fn leave(&mut self, seat: usize) -> Result<(), KostkaError> {
+        if !self.started {
+            self.seats[seat] = Seat::Open;
+            return Ok(());
+        }
+        if self.finished.is_some() {
+            return Ok(());
+        }
+        let on_turn = seat == self.state().my_index();
+        self.game.forfeit(seat)?;
+        if on_turn {
+            self.question = None;
+            self.advance();
+        }
+        Ok(())
+    }
+
+    fn start_when_full(&mut self) {
+        if self.started || self.seats.iter().any(|seat| matches!(seat, Seat::Open)) {
+            return;
+        }
+        self.started = true;
+        self.broadcast(&Event::Started);
+        self.advance();
+    }
+
+    fn broadcast(&self, event: &Event) {
+        let text = serde_json::to_string(event).expect("Events serialize");
+        // nobody may be listening
+        let _ = self.events.send(text);
+    }
+
+    /// Plays the bots' moves until a human is asked or the game ends
+    fn advance(&mut self) {
+        loop {
+            let step = match self.game.step() {
+                Ok(step) => step,
+                Err(_) => return self.finish(None),
+            };
+            let player = self.game.state().my_index();
+            let bot = match &mut self.seats[player] {
+                Seat::Bot { brain, .. } => Some(brain),
+                _ => None,
+            };
+
+            let event = match step {
+                Step::TurnStarted(player) => {
+                    if let Some(bot) = bot {
+                        bot.new_round();
+                    }
+                    Event::TurnStarted { player }
+                }
+                Step::HotDice(streak) => {
+                    if let Some(bot) = bot {
+                        bot.new_dices();
+                    }
+                    Event::HotDice { player, streak }
+                }
+                Step::TurnEnded(player, result) => Event::TurnEnded {
+                    player,
+                    result,
+                    state: self.game.state().clone(),
+                },
+                Step::PickTake(hand) => match bot {
+                    Some(bot) => {
+                        let take = bot.pick_take(self.game.state(), hand);
+                        if self.game.take(take).is_err() {
+                            return self.finish(None);
+                        }
+                        continue;
+                    }
+                    None => {
+                        let question = Event::PickTake {
+                            player,
+                            dices: hand.get_dices().iter().map(|dice| dice.value).collect(),
+                            takes: hand.takes().to_vec(),
+                        };
+                        return self.ask(question);
+                    }
+                },
+                Step::ContinueOrStop => match bot {
+                    Some(bot) => {
+                        let action = bot.continue_or_stop(self.game.state());
+                        let _ = self.game.decide(action);
+                        continue;
+                    }
+                    None => {
+                        let state = self.game.state();
+                        let question = Event::ContinueOrStop {
+                            player,
+                            turn_score: state.turn_score(),
+                            dices_available: state.dices_available(),
+                        };
+                        return self.ask(question);
+                    }
+                },
+                Step::TakeOver => match bot {
+                    Some(bot) => {
+                        let accept = bot.take_over(self.game.state());
+                        let _ = self.game.take_over(accept);
+                        continue;
+                    }
+                    None => {
+                        let (turn_score, dices_available) =
+                            self.game.state().previous_turn().unwrap_or_default();
+                        let question = Event::TakeOver {
+                            player,
+                            turn_score,
+                            dices_available,
+                        };
+                        return self.ask(question);
+                    }
+                },
+                Step::Finished(result) => return self.finish(Some(result)),
+            };
+            self.broadcast(&event);
+        }
+    }
+
+    fn ask(&mut self, question: Event) {
+        self.broadcast(&question);
+        self.question = Some(question);
+    }
+
+    fn finish(&mut self, result: Option<MatchResult>) {
+        self.finished = Some(result);
+        let (winner, team) = winners(result);
+        self.broadcast(&Event::GameOver { winner, team });
+    }
+}
+
+/// Games hosted by one server, each locked on its own
+#[derive(Default)]
+pub struct Lobby {
+    rooms: Mutex<BTreeMap<u64, Arc<Mutex<Room>>>>,
+    next_id: AtomicU64,
+}
+
+impl Lobby {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn rooms(&self) -> MutexGuard<'_, BTreeMap<u64, Arc<Mutex<Room>>>> {
+        self.rooms.lock().expect("Lobby lock poisoned")
+    }
+
+    /// Hosts a new game, returns its id
+    pub fn create(&self, config: &NewGame) -> Result<u64, KostkaError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let room = Room::new(id, config)?;
+        self.rooms().insert(id, Arc::new(Mutex::new(room)));
+        Ok(id)
+    }
+
+    pub fn room(&self, id: u64) -> Result<Arc<Mutex<Room>>, KostkaError> {
+        self.rooms()
+            .get(&id)
+            .cloned()
+            .ok_or(KostkaError::UnknownGame(id))
+    }
+
+    /// Every hosted game, oldest first
+    pub fn list(&self) -> Vec<GameSummary> {
+        let rooms: Vec<_> = self.rooms().values().cloned().collect();
+        rooms
+            .iter()
+            .map(|room| room.lock().expect("Room lock poisoned").summary())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_game(seats: &[&str]) -> NewGame {
+        NewGame {
+            seats: seats.iter().map(|&s| s.into()).collect(),
+            goal: 500,
+            rules: Ruleset::default(),
+            teams: None,
+        }
+    }
+
+    #[test]
+    fn starts_when_full() {
+        let lobby = Lobby::new();
+        let id = lobby.create(&new_game(&["open", "greedy"])).unwrap();
+        let room = lobby.room(id).unwrap();
+        let mut room = room.lock().unwrap();
+
+        assert!(!room.summary().started);
+        let joined = room.join().unwrap();
+
+        assert_eq!(joined.seat, 0);
+        assert_eq!(room.seat_of(&joined.token), Some(0));
+        assert_eq!(room.summary().seats, ["human", "greedy"]);
+        assert!(room.summary().started);
+        assert!(matches!(
+            room.question,
+            Some(Event::PickTake { player: 0, .. })
+        ));
+        assert!(matches!(room.join(), Err(KostkaError::Refused(_))));
+    }
+
+    #[test]
+    fn bots_play_alone() {
+        let lobby = Lobby::new();
+        let id = lobby.create(&new_game(&["greedy", "optimal"])).unwrap();
+
+        let summary = &lobby.list()[0];
+        assert_eq!(summary.id, id);
+        assert!(summary.finished);
+        assert!(summary.winner.is_some());
+    }
+
+    #[test]
+    fn teams_win_together() {
+        let lobby = Lobby::new();
+        let mut config = new_game(&["greedy", "optimal", "greedy", "optimal"]);
+        config.teams = Some(2);
+        let id = lobby.create(&config).unwrap();
+
+        let room = lobby.room(id).unwrap();
+        let room = room.lock().unwrap();
+        let summary = room.summary();
+        assert_eq!(summary.winner, None);
+        let team = summary.team.unwrap();
+        assert!(team < 2);
+        assert!(room.state().team_scores()[team] > 500);
+
+        config.teams = Some(3);
+        assert!(matches!(lobby.create(&config), Err(KostkaError::Teams(_))));
+    }
+
+    #[test]
+    fn only_the_player_on_turn_answers() {
+        let lobby = Lobby::new();
+        let id = lobby.create(&new_game(&["open", "open"])).unwrap();
+        let room = lobby.room(id).unwrap();
+        let mut room = room.lock().unwrap();
+        room.join().unwrap();
+        room.join().unwrap();
+
+        let pick = ClientMessage::Pick { take: Some(0) };
+        assert!(matches!(
+            room.answer(1, pick.clone()),
+            Err(KostkaError::NotAsked)
+        ));
+        let stop = ClientMessage::Action {
+            action: Action::Stop,
+        };
+        assert!(matches!(room.answer(0, stop), Err(KostkaError::NotAsked)));
+        let far = ClientMessage::Pick { take: Some(99) };
+        assert!(matches!(room.answer(0, far), Err(KostkaError::Refused(_))));
+    }
+
+    #[test]
+    fn leaving_forfeits() {
+        let lobby = Lobby::new();
+        let id = lobby.create(&new_game(&["open", "open"])).unwrap();
+        let room = lobby.room(id).unwrap();
+        let mut room = room.lock().unwrap();
+        room.join().unwrap();
+        room.answer(0, ClientMessage::Leave).unwrap();
+        assert_eq!(room.summary().seats, ["open", "open"]);
+
+        room.join().unwrap();
+        room.join().unwrap();
+        room.answer(1, ClientMessage::Leave).unwrap();
+        room.answer(0, ClientMessage::Pick { take: None }).unwrap();
+
+        assert_eq!(room.summary().winner, Some(0));
+    }
+
+    #[test]
+    fn rejects_unknown_seats() {
+        let lobby = Lobby::new();
+
+        assert!(matches!(
+            lobby.create(&new_game(&["open", "external:rm -rf /"])),
+            Err(KostkaError::UnknownPlayer(_))
+        ));
+        assert!(matches!(
+            lobby.create(&new_game(&[])),
+            Err(KostkaError::Refused(_))
+        ));
+        assert!(matches!(lobby.room(7), Err(KostkaError::UnknownGame(7))));
+    }
+}
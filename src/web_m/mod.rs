@@ -0,0 +1,5 @@
+mod lobby;
+pub use lobby::{Event, GameSummary, Joined, Lobby, NewGame, Room, MAX_SEATS};
+
+mod server;
+pub use server::{router, serve};
@@ -0,0 +1,213 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{net::TcpListener, sync::broadcast::error::RecvError};
+
+use super::lobby::{Event, GameSummary, Joined, Lobby, NewGame, Room};
+use crate::{game_m::GameState, net_m::ClientMessage, KostkaError};
+
+/// Routes of the HTTP service:
+///
+/// - `POST /games` creates a game from [`NewGame`], returns `{"id": ...}`
+/// - `GET /games` lists the lobby
+/// - `GET /games/{id}` returns the game and its state
+/// - `POST /games/{id}/join` takes the first open seat, returns [`Joined`]
+/// - `GET /games/{id}/ws?token=...` streams the game's [`Event`]s over a
+///   WebSocket, the seated player answers on it; without a token it only
+///   watches
+pub fn router(lobby: Arc<Lobby>) -> Router {
+    Router::new()
+        .route("/games", post(create_game).get(list_games))
+        .route("/games/{id}", get(show_game))
+        .route("/games/{id}/join", post(join_game))
+        .route("/games/{id}/ws", get(watch_game))
+        .with_state(lobby)
+}
+
+/// Serves a new lobby on `listener` until the process ends
+pub async fn serve(listener: TcpListener) -> std::io::Result<()> {
+    axum::serve(listener, router(Arc::new(Lobby::new()))).await
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for KostkaError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            KostkaError::UnknownGame(_) => StatusCode::NOT_FOUND,
+            KostkaError::Refused(_) | KostkaError::NotAsked => StatusCode::CONFLICT,
+            KostkaError::InvalidTake(_)
+            | KostkaError::InvalidDice(_)
+            | KostkaError::UnknownPlayer(_)
+            | KostkaError::Ruleset(_)
+            | KostkaError::Teams(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = ErrorBody {
+            error: self.to_string(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+fn lock(room: &Mutex<Room>) -> std::sync::MutexGuard<'_, Room> {
+    room.lock().expect("Room lock poisoned")
+}
+
+/// Runs `work` on the blocking pool, rooms play the bots' turns and build
+/// their tables while locked
+async fn blocking<T, F>(work: F) -> Result<T, KostkaError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, KostkaError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| KostkaError::Io(std::io::Error::other(e)))?
+}
+
+#[derive(Serialize, Deserialize)]
+struct Created {
+    id: u64,
+}
+
+async fn create_game(
+    State(lobby): State<Arc<Lobby>>,
+    Json(config): Json<NewGame>,
+) -> Result<(StatusCode, Json<Created>), KostkaError> {
+    let id = blocking(move || lobby.create(&config)).await?;
+    Ok((StatusCode::CREATED, Json(Created { id })))
+}
+
+async fn list_games(
+    State(lobby): State<Arc<Lobby>>,
+) -> Result<Json<Vec<GameSummary>>, KostkaError> {
+    let games = blocking(move || Ok(lobby.list())).await?;
+    Ok(Json(games))
+}
+
+#[derive(Serialize)]
+struct GameView {
+    #[serde(flatten)]
+    game: GameSummary,
+    state: GameState,
+}
+
+async fn show_game(
+    State(lobby): State<Arc<Lobby>>,
+    Path(id): Path<u64>,
+) -> Result<Json<GameView>, KostkaError> {
+    let room = lobby.room(id)?;
+    let view = blocking(move || {
+        let room = lock(&room);
+        Ok(GameView {
+            game: room.summary(),
+            state: room.state().clone(),
+        })
+    })
+    .await?;
+    Ok(Json(view))
+}
+
+async fn join_game(
+    State(lobby): State<Arc<Lobby>>,
+    Path(id): Path<u64>,
+) -> Result<Json<Joined>, KostkaError> {
+    let room = lobby.room(id)?;
+    let joined = blocking(move || lock(&room).join()).await?;
+    Ok(Json(joined))
+}
+
+#[derive(Deserialize)]
+struct WatchParams {
+    token: Option<String>,
+}
+
+async fn watch_game(
+    State(lobby): State<Arc<Lobby>>,
+    Path(id): Path<u64>,
+    Query(params): Query<WatchParams>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, KostkaError> {
+    let room = lobby.room(id)?;
+    let seat = match params.token {
+        Some(token) => {
+            let room = room.clone();
+            let seat = blocking(move || Ok(lock(&room).seat_of(&token))).await?;
+            Some(seat.ok_or_else(|| KostkaError::Refused("unknown token".into()))?)
+        }
+        None => None,
+    };
+    Ok(upgrade.on_upgrade(move |socket| play(socket, room, seat)))
+}
+
+async fn send(socket: &mut WebSocket, event: &Event) -> bool {
+    let text = serde_json::to_string(event).expect("Events serialize");
+    socket.send(Message::Text(text.into())).await.is_ok()
+}
+
+/// Streams the room's events to the socket and hands the seat's messages
+/// to the room, until either side closes
+async fn play(mut socket: WebSocket, room: Arc<Mutex<Room>>, seat: Option<usize>) {
+    let subscribed = {
+        let room = room.clone();
+        blocking(move || Ok(lock(&room).subscribe(seat))).await
+    };
+    let Ok((catch_up, mut events)) = subscribed else {
+        return;
+    };
+    for event in &catch_up {
+        if !send(&mut socket, event).await {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(text) => {
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        return;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            },
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                };
+                let answered = match (seat, serde_json::from_str::<ClientMessage>(&text)) {
+                    (None, _) => Err("spectators cannot play".to_string()),
+                    (_, Err(e)) => Err(e.to_string()),
+                    (Some(seat), Ok(message)) => {
+                        let room = room.clone();
+                        blocking(move || lock(&room).answer(seat, message))
+                            .await
+                            .map_err(|e| e.to_string())
+                    }
+                };
+                if let Err(message) = answered {
+                    if !send(&mut socket, &Event::Error { message }).await {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+/* Exercises the C API of kostka, exits with 1 on the first failed check. */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "kostka.h"
+
+#define CHECK(cond)                                                          \
+  do {                                                                       \
+    if (!(cond)) {                                                           \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,       \
+              #cond);                                                        \
+      exit(1);                                                               \
+    }                                                                        \
+  } while (0)
+
+static void rulesets(void) {
+  KostkaRuleset rules = kostka_ruleset_default();
+  CHECK(rules.dices == 6 && rules.sides == 6 && rules.one == 100);
+  CHECK(kostka_ruleset_validate(&rules) == KOSTKA_STATUS_OK);
+
+  rules.dices = 11;
+  CHECK(kostka_ruleset_validate(&rules) == KOSTKA_STATUS_INVALID_RULESET);
+  CHECK(kostka_ruleset_validate(NULL) == KOSTKA_STATUS_NULL_POINTER);
+  CHECK(strcmp(kostka_status_message(KOSTKA_STATUS_NOT_ASKED),
+               "answered a question that was not asked") == 0);
+  CHECK(strcmp(kostka_status_message(99), "unknown status") == 0);
+  CHECK(strcmp(kostka_status_message(-1), "unknown status") == 0);
+}
+
+static void hand_takes(void) {
+  const int32_t dices[] = {1, 5, 5};
+  size_t count = 0;
+
+  /* ask for the count first */
+  CHECK(kostka_hand_takes(NULL, dices, 3, NULL, 0, &count) ==
+        KOSTKA_STATUS_BUFFER_TOO_SMALL);
+  CHECK(count > 1);
+
+  KostkaTake *takes = malloc(count * sizeof(KostkaTake));
+  CHECK(kostka_hand_takes(NULL, dices, 3, takes, count, &count) ==
+        KOSTKA_STATUS_OK);
+  CHECK(takes[0].value == 200);
+  const uint32_t best[6] = {1, 0, 0, 0, 2, 0};
+  CHECK(memcmp(takes[0].dices_used, best, sizeof(best)) == 0);
+  free(takes);
+
+  const int32_t bust[] = {2, 3};
+  CHECK(kostka_hand_takes(NULL, bust, 2, NULL, 0, &count) == KOSTKA_STATUS_OK);
+  CHECK(count == 0);
+
+  KostkaRuleset four_sides = kostka_ruleset_default();
+  four_sides.sides = 4;
+  CHECK(kostka_hand_takes(&four_sides, dices, 3, NULL, 0, &count) ==
+        KOSTKA_STATUS_INVALID_DICE);
+
+  KostkaRuleset eight_sides = kostka_ruleset_default();
+  eight_sides.sides = 8;
+  const int32_t high[] = {8, 1, 8, 8};
+  KostkaTake high_takes[KOSTKA_MAX_SIDES];
+  CHECK(kostka_hand_takes(&eight_sides, high, 4, high_takes, KOSTKA_MAX_SIDES,
+                          &count) == KOSTKA_STATUS_OK);
+  CHECK(high_takes[0].value == 900);
+  CHECK(high_takes[0].dices_used[0] == 1 && high_takes[0].dices_used[7] == 3);
+  CHECK(high_takes[0].dices_used[KOSTKA_MAX_SIDES - 1] == 0);
+}
+
+static void match_to_the_end(void) {
+  KostkaMatch *game = NULL;
+  CHECK(kostka_match_new(NULL, 2, 300, 7, &game) == KOSTKA_STATUS_OK);
+  CHECK(kostka_match_decide(game, true) == KOSTKA_STATUS_NOT_ASKED);
+
+  KostkaStep step;
+  KostkaTake takes[64];
+  size_t count;
+  for (;;) {
+    CHECK(kostka_match_step(game, &step) == KOSTKA_STATUS_OK);
+    if (step.kind == KOSTKA_STEP_KIND_FINISHED) {
+      break;
+    }
+    switch (step.kind) {
+    case KOSTKA_STEP_KIND_PICK_TAKE:
+      CHECK(step.dices_len > 0 && step.dices_len <= 6);
+      CHECK(kostka_match_takes(game, takes, 64, &count) == KOSTKA_STATUS_OK);
+      CHECK(count == step.takes_len);
+      CHECK(kostka_match_take(game, count) == KOSTKA_STATUS_INVALID_TAKE);
+      if (count > 0) {
+        CHECK(kostka_match_take(game, 0) == KOSTKA_STATUS_OK);
+      } else {
+        CHECK(kostka_match_give_up(game) == KOSTKA_STATUS_OK);
+      }
+      break;
+    case KOSTKA_STEP_KIND_CONTINUE_OR_STOP:
+      CHECK(step.turn_score > 0);
+      CHECK(kostka_match_decide(game, true) == KOSTKA_STATUS_OK);
+      break;
+    default:
+      break;
+    }
+  }
+
+  uint32_t scores[2];
+  CHECK(kostka_match_scores(game, scores, 2, &count) == KOSTKA_STATUS_OK);
+  CHECK(count == 2);
+  CHECK(scores[step.player] > 300);
+  CHECK(kostka_match_takes(game, takes, 64, &count) ==
+        KOSTKA_STATUS_NOT_ASKED);
+
+  kostka_match_free(game);
+  kostka_match_free(NULL);
+}
+
+static void teams(void) {
+  KostkaMatch *game = NULL;
+  CHECK(kostka_match_new(NULL, 3, 300, 7, &game) == KOSTKA_STATUS_OK);
+  CHECK(kostka_match_set_teams(game, 2) == KOSTKA_STATUS_INVALID_TEAMS);
+  kostka_match_free(game);
+
+  CHECK(kostka_match_new(NULL, 4, 300, 7, &game) == KOSTKA_STATUS_OK);
+  CHECK(kostka_match_set_teams(game, 2) == KOSTKA_STATUS_OK);
+  uint32_t scores[2];
+  size_t count;
+  CHECK(kostka_match_team_scores(game, scores, 2, &count) == KOSTKA_STATUS_OK);
+  CHECK(count == 2 && scores[0] == 0);
+
+  KostkaStep step;
+  do {
+    CHECK(kostka_match_step(game, &step) == KOSTKA_STATUS_OK);
+    CHECK(step.kind != KOSTKA_STEP_KIND_FINISHED);
+    if (step.kind == KOSTKA_STEP_KIND_PICK_TAKE) {
+      CHECK((step.takes_len > 0 ? kostka_match_take(game, 0)
+                                : kostka_match_give_up(game)) ==
+            KOSTKA_STATUS_OK);
+    } else if (step.kind == KOSTKA_STEP_KIND_CONTINUE_OR_STOP) {
+      CHECK(kostka_match_decide(game, true) == KOSTKA_STATUS_OK);
+    }
+  } while (step.kind != KOSTKA_STEP_KIND_TEAM_FINISHED);
+  CHECK(step.team < 2);
+  CHECK(kostka_match_team_scores(game, scores, 2, &count) == KOSTKA_STATUS_OK);
+  CHECK(scores[step.team] > 300);
+  kostka_match_free(game);
+}
+
+int main(void) {
+  rulesets();
+  hand_takes();
+  match_to_the_end();
+  teams();
+  return 0;
+}
@@ -0,0 +1,69 @@
+//! Checks the C header and runs the harness in tests/c against the shared
+//! library.
+
+use std::{env, path::PathBuf, process::Command};
+
+const HEADER: &str = "include/kostka.h";
+
+fn crate_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn header_is_current() {
+    let config = cbindgen::Config::from_file(crate_dir().join("cbindgen.toml")).unwrap();
+    let mut generated = vec![];
+    cbindgen::generate_with_config(crate_dir(), config)
+        .expect("Header generated")
+        .write(&mut generated);
+
+    let header = crate_dir().join(HEADER);
+    if env::var_os("KOSTKA_UPDATE_HEADER").is_some() {
+        std::fs::write(&header, &generated).unwrap();
+    }
+    let committed = std::fs::read(&header).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "{} is out of date, rerun with KOSTKA_UPDATE_HEADER=1",
+        HEADER
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn c_harness() {
+    // tests link the rlib, the harness needs the shared library of the
+    // same profile
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().and_then(|deps| deps.parent()).unwrap();
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args(["build", "--lib", "--quiet"])
+        .current_dir(crate_dir());
+    if lib_dir.ends_with("release") {
+        cargo.arg("--release");
+    }
+    assert!(cargo.status().unwrap().success(), "Shared library built");
+
+    let harness = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("harness");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".into());
+    let compiled = Command::new(compiler)
+        .arg(crate_dir().join("tests/c/harness.c"))
+        .arg("-I")
+        .arg(crate_dir().join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .args(["-lkostka", "-Wall", "-Werror", "-o"])
+        .arg(&harness)
+        .status()
+        .unwrap();
+    assert!(compiled.success(), "Harness compiled");
+
+    let output = Command::new(&harness).output().unwrap();
+    assert!(
+        output.status.success(),
+        "Harness failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,122 @@
+//! Bindings for JavaScript, run headless in Node:
+//! `cargo test --target wasm32-unknown-unknown --no-default-features --features wasm --test wasm`
+#![cfg(target_arch = "wasm32")]
+
+use kostka::wasm_m::{JsHand, JsMatch};
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn json(value: JsValue) -> Value {
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn hand_takes() {
+    let hand = JsHand::from_dices(vec![2, 3, 5, 5, 5, 1], JsValue::UNDEFINED).unwrap();
+
+    let takes = json(hand.takes().unwrap());
+    assert_eq!(takes[0]["value"], 600);
+    assert_eq!(
+        takes[0]["dices_used"],
+        serde_json::json!([1, 0, 0, 0, 3, 0])
+    );
+    assert_eq!(takes[0]["breakdown"], "one 1 (100) + three 5s (500)");
+    assert_eq!(hand.best_value(), 600);
+    assert_eq!(hand.dices(), [2, 3, 5, 5, 5, 1]);
+}
+
+#[wasm_bindgen_test]
+fn high_faces() {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    let rules = serde_json::json!({"sides": 10})
+        .serialize(&serializer)
+        .unwrap();
+    let hand = JsHand::from_dices(vec![10, 10, 10], rules).unwrap();
+
+    let takes = json(hand.takes().unwrap());
+    assert_eq!(takes[0]["value"], 1000);
+    assert_eq!(
+        takes[0]["dices_used"],
+        serde_json::json!([0, 0, 0, 0, 0, 0, 0, 0, 0, 3])
+    );
+}
+
+#[wasm_bindgen_test]
+fn rejects_bad_input() {
+    let rules = serde_wasm_bindgen::to_value(&serde_json::json!({"dices": 3})).unwrap();
+
+    assert!(JsHand::from_dices(vec![7], JsValue::UNDEFINED).is_err());
+    assert!(JsHand::from_dices(vec![1, 5, 5], rules.clone()).is_ok());
+    assert!(JsMatch::new(2, 500, &[0; 8], rules).is_err());
+}
+
+#[wasm_bindgen_test]
+fn plays_a_match() {
+    let mut game = JsMatch::new(2, 1000, &[7; 32], JsValue::UNDEFINED).unwrap();
+
+    let winner = loop {
+        let step = json(game.step().unwrap());
+        match step["type"].as_str().unwrap() {
+            "pick_take" => {
+                let bust = step["takes"].as_array().unwrap().is_empty();
+                game.take((!bust).then_some(0)).unwrap();
+            }
+            "continue_or_stop" => game.decide(true).unwrap(),
+            "finished" => break step["winner"].as_u64().unwrap() as usize,
+            _ => {}
+        }
+    };
+
+    let state = json(game.state().unwrap());
+    assert!(state["player_scores"][winner].as_u64().unwrap() > 1000);
+}
+
+#[wasm_bindgen_test]
+fn takes_only_when_asked() {
+    let mut game = JsMatch::new(2, 1000, &[7; 32], JsValue::UNDEFINED).unwrap();
+    let state = json(game.state().unwrap());
+
+    assert!(game.take(Some(0)).is_err());
+    assert_eq!(json(game.state().unwrap()), state);
+    assert_eq!(json(game.step().unwrap())["type"], "turn_started");
+
+    let step = json(game.step().unwrap());
+    assert_eq!(step["type"], "pick_take");
+    let bust = step["takes"].as_array().unwrap().is_empty();
+    game.take((!bust).then_some(0)).unwrap();
+    let state = json(game.state().unwrap());
+
+    assert!(game.take(Some(0)).is_err());
+    assert_eq!(json(game.state().unwrap()), state);
+}
+
+#[wasm_bindgen_test]
+fn takes_over_the_previous_turn() {
+    // a plain object as a page passes, not a Map
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    let rules = serde_json::json!({"piggyback": true})
+        .serialize(&serializer)
+        .unwrap();
+    let mut game = JsMatch::new(2, 1000, &[7; 32], rules).unwrap();
+
+    let offer = loop {
+        let step = json(game.step().unwrap());
+        match step["type"].as_str().unwrap() {
+            "pick_take" => {
+                let bust = step["takes"].as_array().unwrap().is_empty();
+                game.take((!bust).then_some(0)).unwrap();
+            }
+            "continue_or_stop" => game.decide(true).unwrap(),
+            "take_over" => break step,
+            "finished" => panic!("Never offered to take over"),
+            _ => {}
+        }
+    };
+    game.take_over(true).unwrap();
+
+    let state = json(game.state().unwrap());
+    assert_eq!(state["turn_score"], offer["turn_score"]);
+    assert_eq!(state["dices_available"], offer["dices_available"]);
+}
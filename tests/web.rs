@@ -0,0 +1,183 @@
+//! Plays games against the HTTP and WebSocket service on localhost.
+
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use kostka::web_m::{router, Event, GameSummary, Joined, Lobby};
+use reqwest::StatusCode;
+use serde_json::json;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{self, Message},
+};
+
+/// Starts a server on a free port, returns its address
+async fn start() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = router(std::sync::Arc::new(Lobby::new()));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    addr.to_string()
+}
+
+async fn create(client: &reqwest::Client, addr: &str, seats: &[&str]) -> u64 {
+    let response = client
+        .post(format!("http://{}/games", addr))
+        .json(&json!({"seats": seats, "goal": 1000}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let created: serde_json::Value = response.json().await.unwrap();
+    created["id"].as_u64().unwrap()
+}
+
+async fn join(client: &reqwest::Client, addr: &str, id: u64) -> reqwest::Response {
+    client
+        .post(format!("http://{}/games/{}/join", addr, id))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn next_event<S>(socket: &mut S) -> Event
+where
+    S: StreamExt<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    let Some(Ok(Message::Text(text))) = socket.next().await else {
+        panic!("No event");
+    };
+    serde_json::from_str(&text).unwrap()
+}
+
+/// Plays the seat greedily over the WebSocket, returns the winner
+async fn play_seat(addr: String, id: u64, joined: Joined) -> Option<usize> {
+    let url = format!("ws://{}/games/{}/ws?token={}", addr, id, joined.token);
+    let (mut socket, _) = connect_async(url).await.unwrap();
+
+    while let Some(message) = socket.next().await {
+        let Message::Text(text) = message.unwrap() else {
+            continue;
+        };
+        let reply = match serde_json::from_str(&text).unwrap() {
+            Event::Welcome { seat, .. } => {
+                assert_eq!(seat, Some(joined.seat));
+                continue;
+            }
+            Event::PickTake { player, takes, .. } if player == joined.seat => {
+                // null gives up a bust
+                let take = (!takes.is_empty()).then_some(0);
+                json!({"type": "pick", "take": take})
+            }
+            Event::ContinueOrStop { player, .. } if player == joined.seat => {
+                json!({"type": "action", "action": "stop"})
+            }
+            Event::GameOver { winner, .. } => return winner,
+            Event::Error { message } => panic!("Refused: {}", message),
+            _ => continue,
+        };
+        socket
+            .send(Message::Text(reply.to_string().into()))
+            .await
+            .unwrap();
+    }
+    panic!("Stream ended before the game");
+}
+
+#[tokio::test]
+async fn humans_and_bot_play_a_game() {
+    let addr = start().await;
+    let client = reqwest::Client::new();
+    let id = create(&client, &addr, &["open", "open", "greedy"]).await;
+
+    let mut players = vec![];
+    for _ in 0..2 {
+        let joined: Joined = join(&client, &addr, id).await.json().await.unwrap();
+        players.push(tokio::spawn(play_seat(addr.clone(), id, joined)));
+    }
+    let mut winners = vec![];
+    for player in players {
+        let winner = tokio::time::timeout(Duration::from_secs(30), player)
+            .await
+            .expect("Game finished in time")
+            .unwrap();
+        winners.push(winner);
+    }
+
+    let lobby: Vec<GameSummary> = client
+        .get(format!("http://{}/games", addr))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(lobby.len(), 1);
+    assert_eq!(lobby[0].seats, ["human", "human", "greedy"]);
+    assert!(lobby[0].finished);
+    assert_eq!(winners, [lobby[0].winner, lobby[0].winner]);
+    assert!(lobby[0].winner.is_some());
+}
+
+#[tokio::test]
+async fn refuses_bad_requests() {
+    let addr = start().await;
+    let client = reqwest::Client::new();
+    let id = create(&client, &addr, &["open", "optimal"]).await;
+
+    assert_eq!(join(&client, &addr, id).await.status(), StatusCode::OK);
+    assert_eq!(
+        join(&client, &addr, id).await.status(),
+        StatusCode::CONFLICT
+    );
+    assert_eq!(
+        join(&client, &addr, 99).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let response = client
+        .post(format!("http://{}/games", addr))
+        .json(&json!({"seats": ["open", "external:sh"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post(format!("http://{}/games", addr))
+        .json(&json!({"seats": ["optimal"], "rules": {"five": 0}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        join(&client, &addr, id).await.status(),
+        StatusCode::CONFLICT
+    );
+
+    let url = format!("ws://{}/games/{}/ws?token=guess", addr, id);
+    assert!(connect_async(url).await.is_err());
+}
+
+#[tokio::test]
+async fn spectators_watch_only() {
+    let addr = start().await;
+    let client = reqwest::Client::new();
+    let id = create(&client, &addr, &["open", "greedy"]).await;
+    join(&client, &addr, id).await;
+
+    let url = format!("ws://{}/games/{}/ws", addr, id);
+    let (mut socket, _) = connect_async(url).await.unwrap();
+    assert!(matches!(
+        next_event(&mut socket).await,
+        Event::Welcome { seat: None, .. }
+    ));
+    assert!(matches!(
+        next_event(&mut socket).await,
+        Event::PickTake { player: 0, .. }
+    ));
+    let pick = json!({"type": "pick", "take": 0}).to_string();
+    socket.send(Message::Text(pick.into())).await.unwrap();
+    assert!(matches!(next_event(&mut socket).await, Event::Error { .. }));
+}